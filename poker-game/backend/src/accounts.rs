@@ -0,0 +1,330 @@
+//! Player accounts and their play-money bankrolls.
+//!
+//! Chips only enter the game as a grant to a new account. From there they
+//! move between a bankroll and the tables, and every movement is appended
+//! to a ledger at `<dir>/ledger.jsonl`, from which the balances are worked
+//! out on startup. Accounts themselves are appended to
+//! `<dir>/accounts.jsonl`. Neither file is ever rewritten.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::jsonl::{self, Page};
+use crate::lobby::TableId;
+use crate::protocol::{ErrorCode, ProtocolError};
+
+pub type AccountId = u64;
+
+/// Longest display name an account can have.
+const MAX_NAME_LEN: usize = 32;
+/// Where accounts are kept unless `ACCOUNTS_DIR` says otherwise.
+const DEFAULT_DIR: &str = "accounts";
+/// What a new account starts with unless `STARTING_BANKROLL` says otherwise.
+const DEFAULT_STARTING_BANKROLL: u64 = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Account {
+    pub id: AccountId,
+    pub name: String,
+    /// Secret the player joins tables with.
+    pub token: String,
+    pub created_at: u64,
+}
+
+/// An account as anyone may see it.
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountSummary {
+    pub id: AccountId,
+    pub name: String,
+    pub balance: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// Chips given to a new account.
+    Grant,
+    /// Chips taken from the bankroll to sit at a table.
+    BuyIn,
+    /// Chips brought back from a table.
+    CashOut,
+}
+
+/// One movement of chips in or out of a bankroll.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LedgerEntry {
+    pub seq: u64,
+    /// When it happened, in seconds since the Unix epoch.
+    pub at: u64,
+    pub account: AccountId,
+    pub kind: EntryKind,
+    /// Chips into the bankroll, negative for chips out of it.
+    pub amount: i64,
+    pub table_id: Option<TableId>,
+    /// The bankroll afterwards.
+    pub balance: u64,
+}
+
+/// How an account's ledger adds up.
+#[derive(Serialize, Debug, Clone)]
+pub struct AccountAudit {
+    pub id: AccountId,
+    pub name: String,
+    pub balance: u64,
+    /// Whether each entry's balance follows from the one before.
+    pub consistent: bool,
+}
+
+/// The chips the ledger says went to a table against what's on it.
+#[derive(Serialize, Debug, Clone)]
+pub struct TableAudit {
+    pub table_id: TableId,
+    /// Buy-ins less cash-outs.
+    pub ledger_chips: i64,
+    /// Chips in stacks and in the pot, or 0 for a table that's closed.
+    pub table_chips: u64,
+    pub balanced: bool,
+}
+
+/// Reconciliation of every bankroll and table against the ledger.
+#[derive(Serialize, Debug, Clone)]
+pub struct Audit {
+    /// Chips granted to accounts, which is every chip there is.
+    pub granted: u64,
+    pub bankrolls: u64,
+    pub on_tables: u64,
+    /// Whether every account and table adds up, and no chips were made or
+    /// lost between them.
+    pub balanced: bool,
+    pub accounts: Vec<AccountAudit>,
+    pub tables: Vec<TableAudit>,
+}
+
+pub struct Accounts {
+    accounts_path: PathBuf,
+    ledger_path: PathBuf,
+    starting_bankroll: u64,
+    accounts: BTreeMap<AccountId, Account>,
+    balances: HashMap<AccountId, u64>,
+    ledger: Vec<LedgerEntry>,
+}
+
+impl Accounts {
+    /// Opens the accounts in `dir`, creating it if needed, and works out
+    /// every balance from the ledger.
+    pub fn open(dir: impl Into<PathBuf>, starting_bankroll: u64) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let accounts_path = dir.join("accounts.jsonl");
+        let ledger_path = dir.join("ledger.jsonl");
+
+        let accounts = jsonl::read_all::<Account>(&accounts_path)?
+            .into_iter()
+            .map(|(_, account)| (account.id, account))
+            .collect();
+        let ledger: Vec<LedgerEntry> = jsonl::read_all(&ledger_path)?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect();
+        let mut balances: HashMap<AccountId, u64> = HashMap::new();
+        for entry in &ledger {
+            let balance = balances.entry(entry.account).or_default();
+            *balance = balance.saturating_add_signed(entry.amount);
+        }
+        Ok(Accounts { accounts_path, ledger_path, starting_bankroll, accounts, balances, ledger })
+    }
+
+    /// Opens the accounts in `ACCOUNTS_DIR`, granting new ones
+    /// `STARTING_BANKROLL`, falling back to the defaults for anything unset
+    /// or invalid.
+    pub fn from_env() -> io::Result<Self> {
+        let dir = std::env::var("ACCOUNTS_DIR").unwrap_or_else(|_| DEFAULT_DIR.to_string());
+        let starting_bankroll = std::env::var("STARTING_BANKROLL").ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_STARTING_BANKROLL);
+        Accounts::open(dir, starting_bankroll)
+    }
+
+    /// Opens an account under `name` with the starting bankroll.
+    pub fn register(&mut self, name: &str) -> Result<Account, ProtocolError> {
+        let name = name.trim();
+        if name.is_empty() || name.len() > MAX_NAME_LEN {
+            let message = format!("Name must be between 1 and {} characters", MAX_NAME_LEN);
+            return Err(ProtocolError::new(ErrorCode::InvalidName, message));
+        }
+        if self.accounts.values().any(|account| account.name.eq_ignore_ascii_case(name)) {
+            return Err(ProtocolError::new(ErrorCode::NameTaken, "That name is taken"));
+        }
+
+        let account = Account {
+            id: self.accounts.keys().next_back().map_or(1, |id| id + 1),
+            name: name.to_string(),
+            token: format!("{:032x}", rand::random::<u128>()),
+            created_at: now(),
+        };
+        jsonl::append(&self.accounts_path, &account).map_err(bankroll_unavailable)?;
+        self.accounts.insert(account.id, account.clone());
+        let grant = self.starting_bankroll as i64;
+        self.record(account.id, EntryKind::Grant, grant, None).map_err(bankroll_unavailable)?;
+        Ok(account)
+    }
+
+    pub fn by_token(&self, token: &str) -> Option<&Account> {
+        self.accounts.values().find(|account| account.token == token)
+    }
+
+    pub fn summary(&self, id: AccountId) -> Option<AccountSummary> {
+        self.accounts.get(&id).map(|account| AccountSummary {
+            id,
+            name: account.name.clone(),
+            balance: self.balance(id),
+        })
+    }
+
+    pub fn balance(&self, id: AccountId) -> u64 {
+        self.balances.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Moves `chips` from the account's bankroll onto a table.
+    pub fn buy_in(&mut self, id: AccountId, table_id: TableId, chips: u32) -> Result<(), ProtocolError> {
+        let balance = self.balance(id);
+        if balance < u64::from(chips) {
+            let message = format!("Bankroll has only {} chips", balance);
+            return Err(ProtocolError::new(ErrorCode::InsufficientBankroll, message));
+        }
+        self.record(id, EntryKind::BuyIn, -i64::from(chips), Some(table_id))
+            .map_err(bankroll_unavailable)
+    }
+
+    /// Brings `chips` back from a table into the account's bankroll.
+    pub fn cash_out(&mut self, id: AccountId, table_id: TableId, chips: u32) -> io::Result<()> {
+        self.record(id, EntryKind::CashOut, i64::from(chips), Some(table_id))
+    }
+
+    /// Appends a movement to the ledger, then applies it.
+    fn record(&mut self, account: AccountId, kind: EntryKind, amount: i64, table_id: Option<TableId>) -> io::Result<()> {
+        let entry = LedgerEntry {
+            seq: self.ledger.last().map_or(1, |entry| entry.seq + 1),
+            at: now(),
+            account,
+            kind,
+            amount,
+            table_id,
+            balance: self.balance(account).saturating_add_signed(amount),
+        };
+        jsonl::append(&self.ledger_path, &entry)?;
+        self.balances.insert(account, entry.balance);
+        self.ledger.push(entry);
+        Ok(())
+    }
+
+    /// A page of the account's ledger, newest first.
+    pub fn ledger(&self, id: AccountId, offset: usize, limit: usize) -> Page<LedgerEntry> {
+        let entries = self.ledger.iter()
+            .rev()
+            .filter(|entry| entry.account == id)
+            .cloned()
+            .collect();
+        Page::new(entries, offset, limit)
+    }
+
+    /// Highest table id anyone bought in at or cashed out from.
+    pub fn last_table_id(&self) -> Option<TableId> {
+        self.ledger.iter().filter_map(|entry| entry.table_id).max()
+    }
+
+    /// Reconciles the ledger against every bankroll and `table_chips`, the
+    /// chips on each open table.
+    pub fn audit(&self, table_chips: &BTreeMap<TableId, u64>) -> Audit {
+        let mut granted = 0;
+        let mut running: HashMap<AccountId, u64> = HashMap::new();
+        let mut inconsistent = vec![];
+        let mut ledger_chips: BTreeMap<TableId, i64> = table_chips.keys().map(|&id| (id, 0)).collect();
+        for entry in &self.ledger {
+            let balance = running.entry(entry.account).or_default();
+            *balance = balance.saturating_add_signed(entry.amount);
+            if *balance != entry.balance {
+                inconsistent.push(entry.account);
+            }
+            match (entry.kind, entry.table_id) {
+                (EntryKind::Grant, _) => granted += entry.amount.unsigned_abs(),
+                (_, Some(table_id)) => *ledger_chips.entry(table_id).or_default() -= entry.amount,
+                (_, None) => {}
+            }
+        }
+
+        let accounts: Vec<AccountAudit> = self.accounts.values()
+            .map(|account| AccountAudit {
+                id: account.id,
+                name: account.name.clone(),
+                balance: self.balance(account.id),
+                consistent: !inconsistent.contains(&account.id),
+            })
+            .collect();
+        let tables: Vec<TableAudit> = ledger_chips.into_iter()
+            .map(|(table_id, ledger_chips)| {
+                let table_chips = table_chips.get(&table_id).copied().unwrap_or(0);
+                TableAudit { table_id, ledger_chips, table_chips, balanced: ledger_chips == table_chips as i64 }
+            })
+            .collect();
+
+        let bankrolls = self.balances.values().sum();
+        let on_tables = tables.iter().map(|table| table.table_chips).sum();
+        let balanced = accounts.iter().all(|account| account.consistent)
+            && tables.iter().all(|table| table.balanced)
+            && granted == bankrolls + on_tables;
+        Audit { granted, bankrolls, on_tables, balanced, accounts, tables }
+    }
+}
+
+fn bankroll_unavailable(err: io::Error) -> ProtocolError {
+    ProtocolError::new(ErrorCode::BankrollUnavailable, err.to_string())
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory for one test's accounts.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("poker-accounts-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn audit_balances_through_buy_in_play_and_cash_out() {
+        let dir = temp_dir("audit");
+        let mut accounts = Accounts::open(&dir, 1000).unwrap();
+        let alice = accounts.register("Alice").unwrap().id;
+        let bob = accounts.register("Bob").unwrap().id;
+        accounts.buy_in(alice, 1, 200).unwrap();
+        accounts.buy_in(bob, 1, 200).unwrap();
+
+        // Alice wins 50 from Bob, then cashes out what she has
+        accounts.cash_out(alice, 1, 250).unwrap();
+        let audit = accounts.audit(&BTreeMap::from([(1, 150)]));
+        assert!(audit.balanced);
+        assert_eq!((audit.granted, audit.bankrolls, audit.on_tables), (2000, 1850, 150));
+        assert_eq!(accounts.balance(alice), 1050);
+
+        // Chips missing from the table show up
+        assert!(!accounts.audit(&BTreeMap::from([(1, 140)])).balanced);
+
+        // The balances are worked out the same from the ledger on disk
+        let reopened = Accounts::open(&dir, 1000).unwrap();
+        assert_eq!((reopened.balance(alice), reopened.balance(bob)), (1050, 800));
+        assert!(reopened.audit(&BTreeMap::from([(1, 150)])).balanced);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,207 @@
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::accounts::{AccountId, Accounts};
+use crate::archive::{HandArchive, HandId};
+use crate::lobby::{Lobby, TableId};
+use crate::protocol::{ErrorCode, ProtocolError, ServerMessage};
+use crate::server::GetSummary;
+use crate::table::TableSettings;
+
+/// Registers the lobby's REST endpoints.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/tables", web::get().to(list_tables))
+        .route("/tables", web::post().to(create_table))
+        .route("/tables/{table_id}", web::get().to(get_table))
+        .route("/tables/{table_id}", web::delete().to(close_table))
+        .route("/tables/{table_id}/hands", web::get().to(list_hands))
+        .route("/hands/{hand_id}", web::get().to(get_hand))
+        .route("/accounts", web::post().to(create_account))
+        .route("/accounts/{account_id}", web::get().to(get_account))
+        .route("/accounts/{account_id}/ledger", web::get().to(get_ledger))
+        .route("/ledger/audit", web::get().to(audit_ledger));
+}
+
+/// Paging and filtering for a table's hand history.
+#[derive(Deserialize, Debug)]
+struct HandQuery {
+    /// Only hands this player was dealt into, ignoring case.
+    player: Option<String>,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "default_page_size")]
+    limit: usize,
+}
+
+/// Paging for an account's ledger.
+#[derive(Deserialize, Debug)]
+struct LedgerQuery {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "default_page_size")]
+    limit: usize,
+}
+
+#[derive(Deserialize, Debug)]
+struct NewAccount {
+    name: String,
+}
+
+fn default_page_size() -> usize {
+    20
+}
+
+/// How to return a single hand.
+#[derive(Deserialize, Debug)]
+struct HandFormat {
+    /// `pokerstars` for the text export, otherwise JSON.
+    format: Option<String>,
+}
+
+pub fn table_not_found(id: TableId) -> HttpResponse {
+    HttpResponse::NotFound().json(ProtocolError::new(ErrorCode::TableNotFound, format!("No table {}", id)))
+}
+
+fn archive_unavailable(err: std::io::Error) -> HttpResponse {
+    HttpResponse::InternalServerError().json(ProtocolError::new(ErrorCode::ArchiveUnavailable, err.to_string()))
+}
+
+fn account_not_found(id: AccountId) -> HttpResponse {
+    HttpResponse::NotFound().json(ProtocolError::new(ErrorCode::AccountNotFound, format!("No account {}", id)))
+}
+
+fn table_gone() -> HttpResponse {
+    HttpResponse::Gone().json(ProtocolError::new(ErrorCode::TableNotFound, "The table was closed"))
+}
+
+async fn list_tables(lobby: web::Data<Mutex<Lobby>>) -> HttpResponse {
+    // Don't hold the lobby while waiting on the tables
+    let tables = lobby.lock().unwrap().tables();
+    let mut summaries = vec![];
+    for table in tables {
+        if let Ok(summary) = table.send(GetSummary).await {
+            summaries.push(summary);
+        }
+    }
+    HttpResponse::Ok().json(summaries)
+}
+
+async fn create_table(lobby: web::Data<Mutex<Lobby>>, settings: web::Json<TableSettings>) -> HttpResponse {
+    let settings = settings.into_inner();
+    if let Err(message) = settings.validate() {
+        return HttpResponse::BadRequest().json(ProtocolError::new(ErrorCode::InvalidSettings, message));
+    }
+    let (_, table) = lobby.lock().unwrap().open(settings);
+    match table.send(GetSummary).await {
+        Ok(summary) => HttpResponse::Created().json(summary),
+        Err(_) => table_gone(),
+    }
+}
+
+async fn get_table(lobby: web::Data<Mutex<Lobby>>, path: web::Path<TableId>) -> HttpResponse {
+    let id = path.into_inner();
+    let Some(table) = lobby.lock().unwrap().get(id) else {
+        return table_not_found(id);
+    };
+    match table.send(GetSummary).await {
+        Ok(summary) => HttpResponse::Ok().json(summary),
+        Err(_) => table_gone(),
+    }
+}
+
+async fn close_table(lobby: web::Data<Mutex<Lobby>>, path: web::Path<TableId>) -> HttpResponse {
+    let id = path.into_inner();
+    if lobby.lock().unwrap().close(id) {
+        HttpResponse::NoContent().finish()
+    } else {
+        table_not_found(id)
+    }
+}
+
+/// Hands played at a table, newest first. Closed tables keep their history.
+async fn list_hands(
+    archive: web::Data<Mutex<HandArchive>>,
+    path: web::Path<TableId>,
+    query: web::Query<HandQuery>,
+) -> HttpResponse {
+    let query = query.into_inner();
+    let page = archive.lock().unwrap().hands(path.into_inner(), query.player.as_deref(), query.offset, query.limit);
+    HttpResponse::Ok().json(page)
+}
+
+/// A single hand, with only the hole cards that were shown down in its
+/// history. The shuffle comes with it so anyone can check the deal, though
+/// the deck it rebuilds gives away folded hands too, as the reveal sent to
+/// the table at the end of every hand already does.
+async fn get_hand(
+    archive: web::Data<Mutex<HandArchive>>,
+    path: web::Path<HandId>,
+    query: web::Query<HandFormat>,
+) -> HttpResponse {
+    let id = path.into_inner();
+    let mut hand = match archive.lock().unwrap().hand(id) {
+        Ok(Some(hand)) => hand,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(ProtocolError::new(ErrorCode::HandNotFound, format!("No hand {}", id)));
+        }
+        Err(err) => return archive_unavailable(err),
+    };
+    hand.history = hand.history.visible_to(None);
+    match query.format.as_deref() {
+        Some("pokerstars") => HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body(hand.history.to_pokerstars(&hand.table_name, hand.max_seats, None)),
+        _ => HttpResponse::Ok().json(hand),
+    }
+}
+
+/// Opens an account with the starting bankroll. The reply holds the token
+/// to join tables with, so it's only ever sent to whoever registered.
+async fn create_account(accounts: web::Data<Mutex<Accounts>>, body: web::Json<NewAccount>) -> HttpResponse {
+    let mut accounts = accounts.lock().unwrap();
+    match accounts.register(&body.name) {
+        Ok(account) => {
+            let balance = accounts.balance(account.id);
+            HttpResponse::Created().json(ServerMessage::Account { account, balance })
+        }
+        Err(error) if error.code == ErrorCode::BankrollUnavailable => HttpResponse::InternalServerError().json(error),
+        Err(error) => HttpResponse::BadRequest().json(error),
+    }
+}
+
+async fn get_account(accounts: web::Data<Mutex<Accounts>>, path: web::Path<AccountId>) -> HttpResponse {
+    let id = path.into_inner();
+    match accounts.lock().unwrap().summary(id) {
+        Some(summary) => HttpResponse::Ok().json(summary),
+        None => account_not_found(id),
+    }
+}
+
+/// Every chip into and out of an account's bankroll, newest first.
+async fn get_ledger(
+    accounts: web::Data<Mutex<Accounts>>,
+    path: web::Path<AccountId>,
+    query: web::Query<LedgerQuery>,
+) -> HttpResponse {
+    let id = path.into_inner();
+    let accounts = accounts.lock().unwrap();
+    if accounts.summary(id).is_none() {
+        return account_not_found(id);
+    }
+    HttpResponse::Ok().json(accounts.ledger(id, query.offset, query.limit))
+}
+
+/// Reconciles every bankroll and the chips on every open table against the ledger.
+async fn audit_ledger(lobby: web::Data<Mutex<Lobby>>, accounts: web::Data<Mutex<Accounts>>) -> HttpResponse {
+    // Don't hold the lobby while waiting on the tables
+    let tables = lobby.lock().unwrap().tables();
+    let mut table_chips = BTreeMap::new();
+    for table in tables {
+        if let Ok(summary) = table.send(GetSummary).await {
+            table_chips.insert(summary.id, summary.chips);
+        }
+    }
+    HttpResponse::Ok().json(accounts.lock().unwrap().audit(&table_chips))
+}
@@ -0,0 +1,175 @@
+//! Finished hands kept on disk as JSON, so disputes can be settled from the
+//! record.
+//!
+//! Each table's hands are appended to one JSON Lines file per UTC day, at
+//! `<dir>/table-<id>/<YYYY-MM-DD>.jsonl`. The archive keeps an index of
+//! every hand in memory and reads the hand itself from disk when asked.
+
+use poker_core::fair::SeedReveal;
+use poker_core::HandHistory;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::jsonl::{self, Page};
+use crate::lobby::TableId;
+
+pub type HandId = u64;
+
+/// Where hand histories are written unless `HAND_HISTORY_DIR` says otherwise.
+const DEFAULT_DIR: &str = "hand-histories";
+
+/// A finished hand as stored in the archive.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchivedHand {
+    pub hand_id: HandId,
+    pub table_id: TableId,
+    pub table_name: String,
+    pub max_seats: usize,
+    pub history: HandHistory,
+    /// The seeds the hand was shuffled from, to check the deal against.
+    pub shuffle: Option<SeedReveal>,
+}
+
+/// A hand as listed, without the play by play.
+#[derive(Serialize, Debug, Clone)]
+pub struct HandSummary {
+    pub hand_id: HandId,
+    pub table_id: TableId,
+    pub hand_number: u32,
+    pub started_at: u64,
+    pub players: Vec<String>,
+    pub pot: u32,
+}
+
+/// Where to find a hand on disk.
+struct IndexEntry {
+    summary: HandSummary,
+    path: PathBuf,
+    offset: u64,
+}
+
+pub struct HandArchive {
+    dir: PathBuf,
+    /// Every archived hand, oldest first.
+    index: Vec<IndexEntry>,
+    next_hand_id: HandId,
+}
+
+impl HandSummary {
+    fn new(hand: &ArchivedHand) -> Self {
+        let history = &hand.history;
+        HandSummary {
+            hand_id: hand.hand_id,
+            table_id: hand.table_id,
+            hand_number: history.hand_number,
+            started_at: history.started_at,
+            players: history.seats.iter().map(|s| s.name.clone()).collect(),
+            pot: history.total_pot(),
+        }
+    }
+}
+
+impl HandArchive {
+    /// Opens the archive in `dir`, creating it if needed and indexing any
+    /// hands already there.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let mut files = vec![];
+        for table_dir in fs::read_dir(&dir)? {
+            let table_dir = table_dir?.path();
+            if !table_dir.is_dir() {
+                continue;
+            }
+            for file in fs::read_dir(&table_dir)? {
+                let file = file?.path();
+                if file.extension().is_some_and(|ext| ext == "jsonl") {
+                    files.push(file);
+                }
+            }
+        }
+
+        let mut index = vec![];
+        for file in files {
+            index_file(&file, &mut index)?;
+        }
+        index.sort_by_key(|entry| entry.summary.hand_id);
+        let next_hand_id = index.last().map_or(1, |entry| entry.summary.hand_id + 1);
+        Ok(HandArchive { dir, index, next_hand_id })
+    }
+
+    /// Opens the archive in `HAND_HISTORY_DIR`, or `hand-histories` if unset.
+    pub fn from_env() -> io::Result<Self> {
+        let dir = std::env::var("HAND_HISTORY_DIR").unwrap_or_else(|_| DEFAULT_DIR.to_string());
+        HandArchive::open(dir)
+    }
+
+    /// Appends a finished hand to its table's file for the day, returning
+    /// the id it was archived under.
+    pub fn record(
+        &mut self,
+        table_id: TableId,
+        table_name: &str,
+        max_seats: usize,
+        history: HandHistory,
+        shuffle: Option<SeedReveal>,
+    ) -> io::Result<HandId> {
+        let hand = ArchivedHand {
+            hand_id: self.next_hand_id,
+            table_id,
+            table_name: table_name.to_string(),
+            max_seats,
+            history,
+            shuffle,
+        };
+        let table_dir = self.dir.join(format!("table-{}", table_id));
+        fs::create_dir_all(&table_dir)?;
+        let path = table_dir.join(format!("{}.jsonl", hand.history.started_on()));
+        let offset = jsonl::append(&path, &hand)?;
+
+        self.next_hand_id += 1;
+        self.index.push(IndexEntry { summary: HandSummary::new(&hand), path, offset });
+        Ok(hand.hand_id)
+    }
+
+    /// A page of the table's hands, newest first, optionally only those
+    /// `player` was dealt into.
+    pub fn hands(&self, table_id: TableId, player: Option<&str>, offset: usize, limit: usize) -> Page<HandSummary> {
+        let matching = self.index.iter()
+            .rev()
+            .map(|entry| &entry.summary)
+            .filter(|summary| summary.table_id == table_id)
+            .filter(|summary| match player {
+                Some(name) => summary.players.iter().any(|p| p.eq_ignore_ascii_case(name)),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        Page::new(matching, offset, limit)
+    }
+
+    /// Highest table id any hand was archived under.
+    pub fn last_table_id(&self) -> Option<TableId> {
+        self.index.iter().map(|entry| entry.summary.table_id).max()
+    }
+
+    /// Reads a hand back from disk, or `None` if there's no such hand.
+    pub fn hand(&self, hand_id: HandId) -> io::Result<Option<ArchivedHand>> {
+        let Ok(i) = self.index.binary_search_by_key(&hand_id, |entry| entry.summary.hand_id) else {
+            return Ok(None);
+        };
+        let entry = &self.index[i];
+        jsonl::read_at(&entry.path, entry.offset).map(Some)
+    }
+}
+
+/// Adds every hand in a day file to the index.
+fn index_file(path: &Path, index: &mut Vec<IndexEntry>) -> io::Result<()> {
+    for (offset, hand) in jsonl::read_all::<ArchivedHand>(path)? {
+        let summary = HandSummary::new(&hand);
+        index.push(IndexEntry { summary, path: path.to_path_buf(), offset });
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! Append-only JSON Lines files, like the hand archive and the ledger, and
+//! paging through what's read back from them.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Most records returned in one page.
+pub const MAX_PAGE_SIZE: usize = 100;
+
+/// One page of a longer list.
+#[derive(Serialize, Debug, Clone)]
+pub struct Page<T> {
+    /// Records across all pages.
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    pub items: Vec<T>,
+}
+
+impl<T> Page<T> {
+    /// Up to `limit` of `items` starting at `offset`, never more than
+    /// `MAX_PAGE_SIZE`.
+    pub fn new(items: Vec<T>, offset: usize, limit: usize) -> Self {
+        let limit = limit.min(MAX_PAGE_SIZE);
+        Page {
+            total: items.len(),
+            offset,
+            limit,
+            items: items.into_iter().skip(offset).take(limit).collect(),
+        }
+    }
+}
+
+/// Every record in the file with the offset its line starts at. A line that
+/// won't parse, like one cut short by a crash, is skipped, and a file that
+/// doesn't exist yet has no records.
+pub fn read_all<T: DeserializeOwned>(path: &Path) -> io::Result<Vec<(u64, T)>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    let mut records = vec![];
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        if let Ok(record) = serde_json::from_str(line) {
+            records.push((offset, record));
+        }
+        offset += line.len() as u64;
+    }
+    Ok(records)
+}
+
+/// Reads back the record whose line starts at `offset`.
+pub fn read_at<T: DeserializeOwned>(path: &Path, offset: u64) -> io::Result<T> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut line = String::new();
+    BufReader::new(file).read_line(&mut line)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Appends a record and waits for it to reach the disk, returning the
+/// offset its line starts at.
+pub fn append<T: Serialize>(path: &Path, record: &T) -> io::Result<u64> {
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).read(true).append(true).open(path)?;
+    let mut offset = file.seek(SeekFrom::End(0))?;
+    if offset > 0 {
+        // Start a fresh line if a crash left the last one unfinished
+        let mut last = [0];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            line.insert(0, '\n');
+            offset += 1;
+        }
+    }
+    file.write_all(line.as_bytes())?;
+    file.sync_data()?;
+    Ok(offset)
+}
@@ -0,0 +1,106 @@
+use actix::{Actor, Addr};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use crate::accounts::Accounts;
+use crate::archive::HandArchive;
+use crate::server::{CloseTable, TableServer};
+use crate::snapshot::{SnapshotStore, TableSnapshot};
+use crate::table::TableSettings;
+
+pub type TableId = u64;
+
+/// Registry of every table the server is hosting.
+pub struct Lobby {
+    tables: BTreeMap<TableId, Addr<TableServer>>,
+    next_table_id: TableId,
+    /// Where every table archives its finished hands.
+    archive: Arc<Mutex<HandArchive>>,
+    /// Where every table keeps its snapshot.
+    snapshots: Arc<SnapshotStore>,
+    /// Bankrolls every table buys players in from and cashes them out to.
+    accounts: Arc<Mutex<Accounts>>,
+}
+
+impl Lobby {
+    /// Creates an empty lobby. New tables are numbered after the last id
+    /// handed out and any table the archive or the ledger mentions, so a
+    /// closed table's hands and chips are never mistaken for a new one's.
+    pub fn new(archive: Arc<Mutex<HandArchive>>, snapshots: Arc<SnapshotStore>, accounts: Arc<Mutex<Accounts>>) -> std::io::Result<Self> {
+        let last_table_id = [
+            snapshots.last_table_id()?,
+            archive.lock().unwrap().last_table_id(),
+            accounts.lock().unwrap().last_table_id(),
+        ]
+            .into_iter()
+            .flatten()
+            .max()
+            .unwrap_or(0);
+        Ok(Lobby {
+            tables: BTreeMap::new(),
+            next_table_id: last_table_id + 1,
+            archive,
+            snapshots,
+            accounts,
+        })
+    }
+
+    /// Starts a new table with `settings`, which are assumed to be valid.
+    pub fn open(&mut self, settings: TableSettings) -> (TableId, Addr<TableServer>) {
+        let id = self.next_table_id;
+        self.claim_table_id(id);
+        let addr = TableServer::new(id, settings, self.archive.clone(), self.snapshots.clone(), self.accounts.clone()).start();
+        self.tables.insert(id, addr.clone());
+        (id, addr)
+    }
+
+    /// Reopens every table snapshotted before the last shutdown, returning
+    /// how many there were.
+    pub fn restore(&mut self) -> std::io::Result<usize> {
+        let snapshots = self.snapshots.load()?;
+        let restored = snapshots.len();
+        for snapshot in snapshots {
+            self.restore_table(snapshot);
+        }
+        Ok(restored)
+    }
+
+    fn restore_table(&mut self, snapshot: TableSnapshot) {
+        let id = snapshot.id;
+        // Only a snapshot from before the last id was kept can be ahead of it
+        if id >= self.next_table_id {
+            self.claim_table_id(id);
+        }
+        let addr = TableServer::restore(snapshot, self.archive.clone(), self.snapshots.clone(), self.accounts.clone()).start();
+        self.tables.insert(id, addr);
+    }
+
+    /// Records `id` as handed out, so no later table is given it.
+    fn claim_table_id(&mut self, id: TableId) {
+        self.next_table_id = id + 1;
+        if let Err(err) = self.snapshots.save_last_table_id(id) {
+            eprintln!("Couldn't record table id {}: {}", id, err);
+        }
+    }
+
+    pub fn get(&self, id: TableId) -> Option<Addr<TableServer>> {
+        self.tables.get(&id).cloned()
+    }
+
+    /// Every open table, in the order they were opened.
+    pub fn tables(&self) -> Vec<Addr<TableServer>> {
+        self.tables.values().cloned().collect()
+    }
+
+    /// Closes a table and disconnects everyone at it. Returns false if there
+    /// was no such table.
+    pub fn close(&mut self, id: TableId) -> bool {
+        match self.tables.remove(&id) {
+            Some(addr) => {
+                addr.do_send(CloseTable);
+                true
+            }
+            None => false,
+        }
+    }
+}
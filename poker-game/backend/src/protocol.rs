@@ -0,0 +1,239 @@
+//! Websocket protocol between the backend and its clients.
+//!
+//! Every frame is a JSON object with a `type` tag. A client opens with
+//! `{"type": "hello", "version": 2}`; the server replies `welcome` if it
+//! speaks that version, or an `unsupported_version` error and closes the
+//! socket. Nothing else is accepted until the handshake is done.
+//!
+//! After the handshake a client either joins the table, or sends `resume`
+//! with the token from an earlier `seated` message to take back its seat
+//! after a dropped connection. Seats are held for the table's reconnect grace
+//! period.
+//!
+//! Joining takes the token of a player account, which a client without one
+//! can open by sending `register`. The buy-in comes out of the account's
+//! bankroll, and the chips go back to it when the player leaves.
+//!
+//! Seated players act with `fold`, `check`, `call`, `bet` to a total, or
+//! `all_in` with their whole stack. Going all-in is the only way to raise
+//! by less than a full raise. The `legal_actions` in each player's view say
+//! which of these they can make.
+//!
+//! The player to act is on a clock, counted down in `ActionClock` events. Once
+//! it and their time bank run out they check if they can, and fold if not.
+//! Disconnected players get a shorter clock and no time bank.
+//!
+//! Client messages may carry a `request_id` of their choosing, which is
+//! echoed in the `ack` or `error` reply to that message.
+//!
+//! Everything that happens at the table is pushed to every session as an
+//! `event` with a sequence number one higher than the last. A `state`
+//! snapshot, tagged with the sequence number of the last event it includes,
+//! is sent on connect and on joining, so a client can pick up the stream
+//! from there. The player to act is also sent one whenever a new decision
+//! comes up to them, with the `legal_actions` for it.
+//!
+//! ```json
+//! {"type": "register", "name": "Alice", "request_id": 1}
+//! {"type": "account", "id": 7, "name": "Alice", "token": "9f86d081...", "created_at": 1792209354, "balance": 10000}
+//! {"type": "join", "account_token": "9f86d081...", "buy_in": 500, "request_id": 2}
+//! {"type": "bet", "amount": 40, "request_id": 3}
+//! {"type": "error", "request_id": 3, "code": "raise_too_small", "message": "Raise must be to at least 50"}
+//! ```
+
+use poker_core::ActionError;
+use serde::{Deserialize, Serialize};
+
+use crate::accounts::Account;
+use crate::table::{ClockState, TableEvent, TableState};
+
+/// Version of this protocol, bumped on any incompatible change.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Client-chosen id tying a reply to the request it answers.
+pub type RequestId = u64;
+
+/// A client frame: the message plus its optional request id.
+#[derive(Deserialize, Debug)]
+pub struct ClientFrame {
+    #[serde(default)]
+    pub request_id: Option<RequestId>,
+    #[serde(flatten)]
+    pub message: ClientMessage,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Opens the session, naming the protocol version the client speaks.
+    Hello { version: u32 },
+    /// Opens a player account under `name`, answered with an `account`.
+    Register { name: String },
+    /// Takes a seat as the account with `account_token`, buying in for
+    /// `buy_in` chips or the table's maximum.
+    Join {
+        account_token: String,
+        #[serde(default)]
+        buy_in: Option<u32>,
+    },
+    /// Takes seat number `seat`, otherwise like `join`.
+    TakeSeat {
+        seat: usize,
+        account_token: String,
+        #[serde(default)]
+        buy_in: Option<u32>,
+    },
+    /// Stops being dealt in from the next hand, keeping the seat.
+    SitOut,
+    SitIn,
+    /// Gives up the seat, folding if still in the hand. The chips are cashed
+    /// out once the hand is over.
+    LeaveTable,
+    /// Takes back the seat a `seated` message handed out `token` for.
+    Resume { token: String },
+    /// Bet or raise to `amount` in total this round.
+    Bet { amount: u32 },
+    Fold,
+    Check,
+    Call,
+    /// Puts in the player's whole stack, whether that's a call, a bet or a raise.
+    AllIn,
+    Deal,
+    /// Randomness to mix into the next hand's shuffle. Each seated player
+    /// gets one seed per hand, so sending another replaces the last.
+    ClientSeed { seed: String },
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage<'a> {
+    /// Handshake accepted.
+    Welcome { version: u32, session_id: usize },
+    /// The request was applied; any events it caused follow.
+    Ack { request_id: Option<RequestId> },
+    /// This session is playing `seat`. Reconnect with `resume_token` to take
+    /// the seat back if the connection drops.
+    Seated { seat: usize, resume_token: String },
+    /// A newly opened account. Keep `token` to join tables with it.
+    Account {
+        #[serde(flatten)]
+        account: Account,
+        balance: u64,
+    },
+    Error {
+        request_id: Option<RequestId>,
+        code: ErrorCode,
+        message: String,
+    },
+    /// Something that happened at the table.
+    Event { seq: u64, event: TableEvent },
+    /// The table as this session may see it, after event `seq`.
+    State {
+        seq: u64,
+        /// Seated players nobody is connected as.
+        disconnected: Vec<usize>,
+        /// The clock on the player to act, if the table has one.
+        action_clock: Option<ClockState>,
+        #[serde(flatten)]
+        state: TableState<'a>,
+    },
+    /// The table was closed; the socket closes after this.
+    TableClosed,
+}
+
+impl ServerMessage<'_> {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Machine readable reason a request was rejected.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    InvalidMessage,
+    UnsupportedVersion,
+    HandshakeRequired,
+    AlreadyJoined,
+    NotJoined,
+    InvalidName,
+    TableFull,
+    InvalidSeat,
+    SeatTaken,
+    InvalidBuyIn,
+    InvalidResumeToken,
+    InvalidAccountToken,
+    NameTaken,
+    InsufficientBankroll,
+    BankrollUnavailable,
+    AccountNotFound,
+    TableNotFound,
+    InvalidSettings,
+    HandNotFound,
+    ArchiveUnavailable,
+    CannotDeal,
+    SeedRejected,
+    HandNotInProgress,
+    NotYourTurn,
+    InsufficientChips,
+    CannotCheckFacingBet,
+    NothingToCall,
+    RaiseTooSmall,
+    RaiseTooLarge,
+    RaiseCapReached,
+    BettingNotReopened,
+}
+
+/// A rejected request, sent back to the client as an `error` message or as
+/// the body of a failed REST call.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProtocolError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ProtocolError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        ProtocolError {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn reply(self, request_id: Option<RequestId>) -> ServerMessage<'static> {
+        ServerMessage::Error {
+            request_id,
+            code: self.code,
+            message: self.message,
+        }
+    }
+}
+
+impl From<ActionError> for ProtocolError {
+    fn from(error: ActionError) -> Self {
+        let code = match error {
+            ActionError::HandNotInProgress => ErrorCode::HandNotInProgress,
+            ActionError::UnknownPlayer => ErrorCode::NotJoined,
+            ActionError::NotYourTurn => ErrorCode::NotYourTurn,
+            ActionError::InsufficientChips => ErrorCode::InsufficientChips,
+            ActionError::CannotCheckFacingBet => ErrorCode::CannotCheckFacingBet,
+            ActionError::NothingToCall => ErrorCode::NothingToCall,
+            ActionError::RaiseTooSmall { .. } => ErrorCode::RaiseTooSmall,
+            ActionError::RaiseTooLarge { .. } => ErrorCode::RaiseTooLarge,
+            ActionError::RaiseCapReached => ErrorCode::RaiseCapReached,
+            ActionError::BettingNotReopened => ErrorCode::BettingNotReopened,
+            ActionError::SeatTaken => ErrorCode::SeatTaken,
+        };
+        ProtocolError::new(code, error.to_string())
+    }
+}
+
+/// Parses a client frame, keeping the request id if the rest is malformed
+/// so the error can still be matched to the request.
+pub fn parse_client_frame(text: &str) -> Result<ClientFrame, (Option<RequestId>, ProtocolError)> {
+    let value: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| (None, ProtocolError::new(ErrorCode::InvalidMessage, e.to_string())))?;
+    let request_id = value.get("request_id").and_then(|id| id.as_u64());
+    serde_json::from_value(value)
+        .map_err(|e| (request_id, ProtocolError::new(ErrorCode::InvalidMessage, e.to_string())))
+}
@@ -0,0 +1,656 @@
+use actix::{Actor, ActorContext, Addr, AsyncContext, Context, Handler, Message, MessageResult, SpawnHandle};
+use poker_core::{GameEvent, GameState, Round, SeatStatus};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::accounts::{AccountId, Accounts};
+use crate::archive::HandArchive;
+use crate::lobby::TableId;
+use crate::protocol::{ClientMessage, ErrorCode, ProtocolError, RequestId, ServerMessage};
+use crate::session::WsSession;
+use crate::snapshot::{OwedCashOut, SnapshotStore, TableSnapshot};
+use crate::table::{ClockState, Table, TableEvent, TableSettings};
+
+/// Text pushed from the table to a session's websocket.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SendText(pub String);
+
+/// Tells a session the table has gone and it should close.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct CloseSession;
+
+/// A new websocket session subscribing to the table. Returns its session id.
+#[derive(Message)]
+#[rtype(result = "usize")]
+pub struct Connect {
+    pub addr: Addr<WsSession>,
+}
+
+/// A websocket session going away.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Disconnect {
+    pub id: usize,
+}
+
+/// A message received from a session's client.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SessionMessage {
+    pub id: usize,
+    pub request_id: Option<RequestId>,
+    pub message: ClientMessage,
+}
+
+/// Asks a table for its lobby listing.
+#[derive(Message)]
+#[rtype(result = "TableSummary")]
+pub struct GetSummary;
+
+/// Closes the table, disconnecting everyone at it.
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct CloseTable;
+
+/// The countdown on the current decision, ticking once a second.
+struct ActionClock {
+    /// Hand, round and player, so a new decision gets a fresh clock.
+    decision: (u32, Round, usize),
+    /// Whether the player is connected, which decides if they get their
+    /// time bank.
+    connected: bool,
+    state: ClockState,
+    handle: SpawnHandle,
+}
+
+/// A table as listed in the lobby.
+#[derive(Serialize, Debug, Clone)]
+pub struct TableSummary {
+    pub id: TableId,
+    pub settings: TableSettings,
+    pub players: usize,
+    /// Chips in stacks and in the pot.
+    pub chips: u64,
+}
+
+/// Actor owning a table, which every session talks to and which pushes
+/// state to all subscribed sessions whenever it changes.
+pub struct TableServer {
+    id: TableId,
+    table: Table,
+    sessions: HashMap<usize, Addr<WsSession>>,
+    /// Player each session is seated as, once it has joined.
+    players: HashMap<usize, usize>,
+    next_session_id: usize,
+    /// Sequence number of the last event sent.
+    seq: u64,
+    /// Token each seated player can reclaim their seat with, by token.
+    resume_tokens: HashMap<String, usize>,
+    /// Grace period timers for players whose connection dropped, by player.
+    reconnect_timers: HashMap<usize, SpawnHandle>,
+    /// Time bank seconds each player has left, by player.
+    time_banks: HashMap<usize, u64>,
+    action_clock: Option<ActionClock>,
+    /// Hand, round and player of the decision the table is waiting on.
+    turn: Option<(u32, Round, usize)>,
+    /// Account each seat was bought in by, until its chips are cashed out.
+    seat_accounts: HashMap<usize, AccountId>,
+    archive: Arc<Mutex<HandArchive>>,
+    snapshots: Arc<SnapshotStore>,
+    accounts: Arc<Mutex<Accounts>>,
+}
+
+impl TableServer {
+    pub fn new(
+        id: TableId,
+        settings: TableSettings,
+        archive: Arc<Mutex<HandArchive>>,
+        snapshots: Arc<SnapshotStore>,
+        accounts: Arc<Mutex<Accounts>>,
+    ) -> Self {
+        TableServer {
+            id,
+            table: Table::new(settings),
+            sessions: HashMap::new(),
+            players: HashMap::new(),
+            next_session_id: 0,
+            seq: 0,
+            resume_tokens: HashMap::new(),
+            reconnect_timers: HashMap::new(),
+            time_banks: HashMap::new(),
+            action_clock: None,
+            turn: None,
+            seat_accounts: HashMap::new(),
+            archive,
+            snapshots,
+            accounts,
+        }
+    }
+
+    /// Brings a table back from its snapshot. Everyone seated has the
+    /// reconnect grace period to come back with their resume token, and
+    /// anyone who left during the voided hand gets their chips back.
+    pub fn restore(
+        snapshot: TableSnapshot,
+        archive: Arc<Mutex<HandArchive>>,
+        snapshots: Arc<SnapshotStore>,
+        accounts: Arc<Mutex<Accounts>>,
+    ) -> Self {
+        let mut server = TableServer::new(snapshot.id, snapshot.settings, archive, snapshots, accounts);
+        let game = snapshot.game;
+        let seated = |player: &usize| game.players.get(*player).is_some_and(|p| p.status != SeatStatus::Empty);
+        server.resume_tokens = snapshot.resume_tokens.into_iter().filter(|(_, player)| seated(player)).collect();
+        server.time_banks = snapshot.time_banks.into_iter().filter(|(player, _)| seated(player)).collect();
+        server.seat_accounts = snapshot.seat_accounts.into_iter().filter(|(player, _)| seated(player)).collect();
+        server.table.game = game;
+        for owed in snapshot.owed {
+            server.credit(owed.account, owed.chips);
+        }
+        server
+    }
+
+    /// Writes the table to disk, as it was before the hand in progress if
+    /// there is one.
+    fn save_snapshot(&self) {
+        let mut game = self.table.game.clone();
+        game.events.clear();
+        game.void_hand();
+        // Voiding the hand cashes out anyone who left during it
+        let owed = game.events.drain(..)
+            .filter_map(|event| match event {
+                GameEvent::PlayerLeft { player, chips } => self.seat_accounts.get(&player)
+                    .map(|&account| OwedCashOut { account, chips }),
+                _ => None,
+            })
+            .collect();
+        let snapshot = TableSnapshot {
+            id: self.id,
+            settings: self.table.settings.clone(),
+            game,
+            resume_tokens: self.resume_tokens.clone(),
+            time_banks: self.time_banks.clone(),
+            seat_accounts: self.seat_accounts.clone(),
+            owed,
+        };
+        if let Err(err) = self.snapshots.save(&snapshot) {
+            eprintln!("Couldn't snapshot table {}: {}", self.id, err);
+        }
+    }
+
+    /// Holds a seat with nobody connected to it for the grace period.
+    fn start_grace_timer(&mut self, player: usize, ctx: &mut Context<Self>) {
+        let grace = Duration::from_secs(self.table.settings.reconnect_grace_secs);
+        let timer = ctx.run_later(grace, move |act, ctx| act.expire_seat(player, ctx));
+        self.reconnect_timers.insert(player, timer);
+    }
+
+    /// The table state as the session's player may see it.
+    fn state_json(&self, id: usize) -> String {
+        let viewer = self.players.get(&id).copied();
+        let disconnected = (0..self.table.game.players.len())
+            .filter(|&player| self.table.game.players[player].status != SeatStatus::Empty)
+            .filter(|&player| self.session_for(player).is_none())
+            .collect();
+        let action_clock = self.action_clock.as_ref().map(|clock| clock.state.clone());
+        ServerMessage::State {
+            seq: self.seq,
+            disconnected,
+            action_clock,
+            state: self.table.state(viewer),
+        }
+        .to_json()
+    }
+
+    /// The session currently playing `player`'s seat, if any.
+    fn session_for(&self, player: usize) -> Option<usize> {
+        self.players.iter().find(|&(_, &p)| p == player).map(|(&id, _)| id)
+    }
+
+    fn resume_token_for(&self, player: usize) -> Option<&String> {
+        self.resume_tokens.iter().find(|&(_, &p)| p == player).map(|(token, _)| token)
+    }
+
+    /// Gives a dropped player's seat up for good once the grace period is over.
+    fn expire_seat(&mut self, player: usize, ctx: &mut Context<Self>) {
+        self.reconnect_timers.remove(&player);
+        self.table.record(TableEvent::ReconnectExpired { player });
+        let _ = self.table.game.leave_table(player);
+        self.unseat(player);
+        self.table.finish_hand_if_over();
+        self.publish_events();
+        self.schedule_clock(ctx);
+        self.save_snapshot();
+    }
+
+    /// Starts the clock when a new decision comes up, and stops it when
+    /// nobody is to act.
+    ///
+    /// Connected players get the table's action time and then their time
+    /// bank. Disconnected players only get the disconnected time bank.
+    /// Dropping or coming back mid-decision never adds time to the clock.
+    fn schedule_clock(&mut self, ctx: &mut Context<Self>) {
+        let game = &self.table.game;
+        let decision = (game.state == GameState::PlayerTurn)
+            .then(|| (game.hand_number, game.round.clone(), game.current_player_index));
+        let connected = decision.as_ref().is_some_and(|&(_, _, player)| self.session_for(player).is_some());
+
+        // The player to act needs to know what they can do about it
+        if decision != self.turn {
+            self.turn = decision.clone();
+            if let Some(id) = decision.as_ref().and_then(|&(_, _, player)| self.session_for(player)) {
+                self.send_state(id);
+            }
+        }
+
+        // Keep a running clock if it's still for the same decision
+        if let Some(mut clock) = self.action_clock.take() {
+            if decision.as_ref() == Some(&clock.decision) {
+                if clock.connected != connected {
+                    clock.connected = connected;
+                    if !connected {
+                        // Cut down to the disconnected time bank, and stop
+                        // spending their own
+                        let limit = self.table.settings.disconnected_time_bank_secs.max(1);
+                        clock.state.seconds_left = clock.state.seconds_left.min(limit);
+                        clock.state.in_time_bank = false;
+                    }
+                    self.table.record(TableEvent::ActionClock(clock.state.clone()));
+                    self.action_clock = Some(clock);
+                    self.publish_events();
+                    return;
+                }
+                self.action_clock = Some(clock);
+                return;
+            }
+            ctx.cancel_future(clock.handle);
+        }
+        let Some(decision) = decision else {
+            return;
+        };
+
+        let player = decision.2;
+        let settings = &self.table.settings;
+        let seconds = if connected {
+            settings.action_time_secs
+        } else {
+            settings.disconnected_time_bank_secs.max(1)
+        };
+        if seconds == 0 {
+            return;
+        }
+        let state = ClockState { player, seconds_left: seconds, in_time_bank: false };
+        let handle = ctx.run_interval(Duration::from_secs(1), |act, ctx| act.tick_clock(ctx));
+        self.table.record(TableEvent::ActionClock(state.clone()));
+        self.action_clock = Some(ActionClock { decision, connected, state, handle });
+        self.publish_events();
+    }
+
+    fn tick_clock(&mut self, ctx: &mut Context<Self>) {
+        let Some(clock) = self.action_clock.as_mut() else {
+            return;
+        };
+        let player = clock.state.player;
+        let connected = clock.connected;
+        clock.state.seconds_left = clock.state.seconds_left.saturating_sub(1);
+        if clock.state.in_time_bank {
+            if let Some(bank) = self.time_banks.get_mut(&player) {
+                *bank = bank.saturating_sub(1);
+            }
+        }
+
+        let seconds_left = clock.state.seconds_left;
+        if seconds_left > 0 {
+            // Count down every five seconds, then every second at the end
+            if seconds_left <= 5 || seconds_left % 5 == 0 {
+                self.table.record(TableEvent::ActionClock(clock.state.clone()));
+                self.publish_events();
+            }
+            return;
+        }
+
+        let bank = self.time_banks.get(&player).copied().unwrap_or(0);
+        if connected && !clock.state.in_time_bank && bank > 0 {
+            clock.state.in_time_bank = true;
+            clock.state.seconds_left = bank;
+            self.table.record(TableEvent::ActionClock(clock.state.clone()));
+            self.publish_events();
+            return;
+        }
+
+        // Out of time: check if that's free, otherwise fold
+        if let Some(clock) = self.action_clock.take() {
+            ctx.cancel_future(clock.handle);
+        }
+        let game = &mut self.table.game;
+        if game.player_check(player).is_err() {
+            let _ = game.player_fold(player);
+        }
+        self.table.finish_hand_if_over();
+        self.publish_events();
+        self.schedule_clock(ctx);
+    }
+
+    fn send(&self, id: usize, message: ServerMessage) {
+        if let Some(addr) = self.sessions.get(&id) {
+            addr.do_send(SendText(message.to_json()));
+        }
+    }
+
+    fn send_state(&self, id: usize) {
+        if let Some(addr) = self.sessions.get(&id) {
+            addr.do_send(SendText(self.state_json(id)));
+        }
+    }
+
+    /// Writes the hand that just finished, if any, to the archive, and
+    /// snapshots the table now its chips have settled.
+    fn archive_finished_hand(&mut self) {
+        let Some(history) = self.table.game.last_hand.take() else {
+            return;
+        };
+        let shuffle = self.table.shuffle.last_reveal.clone()
+            .filter(|reveal| reveal.hand_number == history.hand_number);
+        let settings = &self.table.settings;
+        let result = self.archive.lock().unwrap()
+            .record(self.id, &settings.name, settings.max_seats, history, shuffle);
+        if let Err(err) = result {
+            eprintln!("Couldn't archive a hand from table {}: {}", self.id, err);
+        }
+        self.save_snapshot();
+    }
+
+    /// Puts chips that came off the table back in the account's bankroll.
+    fn credit(&self, account: AccountId, chips: u32) {
+        if let Err(err) = self.accounts.lock().unwrap().cash_out(account, self.id, chips) {
+            eprintln!("Couldn't cash out {} chips from table {} to account {}: {}", chips, self.id, account, err);
+        }
+    }
+
+    /// Numbers the table's new events and sends them to every session.
+    fn publish_events(&mut self) {
+        let events = self.table.drain_events();
+        // Every change goes out through here, so it's where players' chips are
+        // cashed out and finished hands are noticed
+        for event in &events {
+            if let TableEvent::Game(GameEvent::PlayerLeft { player, chips }) = event {
+                if let Some(account) = self.seat_accounts.remove(player) {
+                    self.credit(account, *chips);
+                }
+            }
+        }
+        self.archive_finished_hand();
+        for event in events {
+            self.seq += 1;
+            // Each session gets its own copy so nobody sees another's hole cards
+            for (id, addr) in &self.sessions {
+                let viewer = self.players.get(id).copied();
+                let message = ServerMessage::Event { seq: self.seq, event: event.visible_to(viewer) };
+                addr.do_send(SendText(message.to_json()));
+            }
+        }
+    }
+
+    /// Seats the session's player at `seat`, or the first free seat, as the
+    /// account with `account_token`, buying in for `buy_in` chips or the
+    /// table's maximum.
+    fn seat_session(
+        &mut self,
+        id: usize,
+        seat: Option<usize>,
+        account_token: &str,
+        buy_in: Option<u32>,
+    ) -> Result<(), ProtocolError> {
+        let (account, name) = self.accounts.lock().unwrap().by_token(account_token)
+            .map(|account| (account.id, account.name.clone()))
+            .ok_or_else(|| ProtocolError::new(ErrorCode::InvalidAccountToken, "Unknown account token"))?;
+        if self.seat_accounts.values().any(|&a| a == account) {
+            return Err(ProtocolError::new(ErrorCode::AlreadyJoined, "That account is already at this table"));
+        }
+        let settings = &self.table.settings;
+        if self.seated_players() >= settings.max_seats {
+            return Err(ProtocolError::new(ErrorCode::TableFull, "Every seat is taken"));
+        }
+        if seat.is_some_and(|seat| seat >= settings.max_seats) {
+            let message = format!("Seats are numbered 0 to {}", settings.max_seats - 1);
+            return Err(ProtocolError::new(ErrorCode::InvalidSeat, message));
+        }
+        let chips = buy_in.unwrap_or(settings.max_buy_in);
+        if !settings.allows_buy_in(chips) {
+            let message = format!("Buy-in must be between {} and {}", settings.min_buy_in, settings.max_buy_in);
+            return Err(ProtocolError::new(ErrorCode::InvalidBuyIn, message));
+        }
+        // Check the seat is free before the chips leave the bankroll
+        if seat.is_some_and(|seat| self.table.game.players.get(seat).is_some_and(|p| p.status != SeatStatus::Empty)) {
+            return Err(ProtocolError::new(ErrorCode::SeatTaken, "That seat is taken"));
+        }
+        self.accounts.lock().unwrap().buy_in(account, self.id, chips)?;
+
+        let seat = match seat {
+            Some(seat) => {
+                self.table.game.take_seat(seat, name, chips)?;
+                seat
+            }
+            None => self.table.game.add_player(name, chips),
+        };
+        self.players.insert(id, seat);
+        self.resume_tokens.insert(format!("{:032x}", rand::random::<u128>()), seat);
+        self.time_banks.insert(seat, self.table.settings.time_bank_secs);
+        self.seat_accounts.insert(seat, account);
+        Ok(())
+    }
+
+    /// Forgets everything tying a session to a seat the player has given up.
+    fn unseat(&mut self, player: usize) {
+        self.players.retain(|_, &mut p| p != player);
+        self.resume_tokens.retain(|_, &mut p| p != player);
+        self.time_banks.remove(&player);
+    }
+
+    fn seated_players(&self) -> usize {
+        self.table.game.players.iter()
+            .filter(|p| p.status != SeatStatus::Empty)
+            .count()
+    }
+
+    /// Applies a client message on behalf of the session's player, returning
+    /// the reason if it was rejected.
+    fn apply(&mut self, id: usize, message: ClientMessage, ctx: &mut Context<Self>) -> Result<(), ProtocolError> {
+        // Game actions always apply to the player bound to this session
+        let player = self.players.get(&id).copied()
+            .ok_or_else(|| ProtocolError::new(ErrorCode::NotJoined, "Join the table first"));
+        let result = match message {
+            ClientMessage::Hello { .. } => {
+                // Sessions only register with the table after the handshake
+                return Err(ProtocolError::new(ErrorCode::InvalidMessage, "Already said hello"));
+            }
+            ClientMessage::Register { name } => {
+                let mut accounts = self.accounts.lock().unwrap();
+                let account = accounts.register(&name)?;
+                let balance = accounts.balance(account.id);
+                drop(accounts);
+                self.send(id, ServerMessage::Account { account, balance });
+                return Ok(());
+            }
+            ClientMessage::Join { account_token, buy_in } => {
+                if player.is_ok() {
+                    return Err(ProtocolError::new(ErrorCode::AlreadyJoined, "Already joined this table"));
+                }
+                return self.seat_session(id, None, &account_token, buy_in);
+            }
+            ClientMessage::TakeSeat { seat, account_token, buy_in } => {
+                if player.is_ok() {
+                    return Err(ProtocolError::new(ErrorCode::AlreadyJoined, "Already joined this table"));
+                }
+                return self.seat_session(id, Some(seat), &account_token, buy_in);
+            }
+            ClientMessage::Resume { token } => {
+                // Re-attach this session to the seat the token was issued for
+                if player.is_ok() {
+                    return Err(ProtocolError::new(ErrorCode::AlreadyJoined, "Already joined this table"));
+                }
+                let seat = *self.resume_tokens.get(&token)
+                    .ok_or_else(|| ProtocolError::new(ErrorCode::InvalidResumeToken, "Unknown or expired resume token"))?;
+                if let Some(old_session) = self.session_for(seat) {
+                    // The old connection hasn't noticed it dropped yet, so replace it
+                    self.players.remove(&old_session);
+                    if let Some(addr) = self.sessions.remove(&old_session) {
+                        addr.do_send(CloseSession);
+                    }
+                } else if let Some(timer) = self.reconnect_timers.remove(&seat) {
+                    ctx.cancel_future(timer);
+                }
+                self.players.insert(id, seat);
+                self.table.record(TableEvent::PlayerReconnected { player: seat });
+                return Ok(());
+            }
+            ClientMessage::Bet { amount } => self.table.game.player_bet(player?, amount).map(|_| ()),
+            ClientMessage::Fold => self.table.game.player_fold(player?).map(|_| ()),
+            ClientMessage::Check => self.table.game.player_check(player?).map(|_| ()),
+            ClientMessage::Call => self.table.game.player_call(player?).map(|_| ()),
+            ClientMessage::AllIn => self.table.game.player_all_in(player?).map(|_| ()),
+            ClientMessage::SitOut => self.table.game.sit_out(player?),
+            ClientMessage::SitIn => self.table.game.sit_in(player?),
+            ClientMessage::LeaveTable => {
+                // Folds if need be; the chips come off the table once the hand is over
+                let player = player?;
+                self.table.game.leave_table(player)?;
+                self.unseat(player);
+                Ok(())
+            }
+            ClientMessage::Deal => {
+                // Start the next hand from the committed shuffle
+                player?;
+                if !self.table.deal() {
+                    return Err(ProtocolError::new(ErrorCode::CannotDeal, "Cannot deal a new hand now"));
+                }
+                return Ok(());
+            }
+            ClientMessage::ClientSeed { seed } => {
+                // Mix the player's seed into the next hand's shuffle. Only
+                // seated players get a say, one seed each per hand
+                if !self.table.shuffle.add_client_seed(player?, seed) {
+                    return Err(ProtocolError::new(ErrorCode::SeedRejected, "Client seed rejected"));
+                }
+                return Ok(());
+            }
+        };
+        self.table.finish_hand_if_over();
+        result?;
+        Ok(())
+    }
+}
+
+impl Actor for TableServer {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Context<Self>) {
+        self.save_snapshot();
+        ctx.run_interval(self.snapshots.interval, |act, _| act.save_snapshot());
+
+        // A restored table has players seated with nobody connected yet
+        let seated: Vec<usize> = (0..self.table.game.players.len())
+            .filter(|&player| self.table.game.players[player].status != SeatStatus::Empty)
+            .collect();
+        for player in seated {
+            self.start_grace_timer(player, ctx);
+        }
+    }
+}
+
+impl Handler<Connect> for TableServer {
+    type Result = usize;
+
+    fn handle(&mut self, msg: Connect, _: &mut Context<Self>) -> usize {
+        let id = self.next_session_id;
+        self.next_session_id += 1;
+        self.sessions.insert(id, msg.addr);
+        self.send_state(id);
+        id
+    }
+}
+
+impl Handler<GetSummary> for TableServer {
+    type Result = MessageResult<GetSummary>;
+
+    fn handle(&mut self, _: GetSummary, _: &mut Context<Self>) -> Self::Result {
+        MessageResult(TableSummary {
+            id: self.id,
+            settings: self.table.settings.clone(),
+            players: self.seated_players(),
+            chips: self.table.game.players.iter().map(|p| u64::from(p.chips)).sum::<u64>() + u64::from(self.table.game.pot),
+        })
+    }
+}
+
+impl Handler<CloseTable> for TableServer {
+    type Result = ();
+
+    fn handle(&mut self, _: CloseTable, ctx: &mut Context<Self>) {
+        // Everyone's chips go back to their bankrolls, as of the start of any
+        // hand in progress
+        self.table.game.void_hand();
+        for player in 0..self.table.game.players.len() {
+            if self.table.game.players[player].status != SeatStatus::Empty {
+                let _ = self.table.game.leave_table(player);
+            }
+        }
+        self.publish_events();
+        for addr in self.sessions.values() {
+            addr.do_send(SendText(ServerMessage::TableClosed.to_json()));
+            addr.do_send(CloseSession);
+        }
+        self.sessions.clear();
+        if let Err(err) = self.snapshots.remove(self.id) {
+            eprintln!("Couldn't remove the snapshot of table {}: {}", self.id, err);
+        }
+        ctx.stop();
+    }
+}
+
+impl Handler<Disconnect> for TableServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: Disconnect, ctx: &mut Context<Self>) {
+        self.sessions.remove(&msg.id);
+        if let Some(player) = self.players.remove(&msg.id) {
+            // Hold the seat for a while in case they come back
+            self.start_grace_timer(player, ctx);
+            self.table.record(TableEvent::PlayerDisconnected { player });
+            self.publish_events();
+            self.schedule_clock(ctx);
+        }
+    }
+}
+
+impl Handler<SessionMessage> for TableServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: SessionMessage, ctx: &mut Context<Self>) {
+        let seat_before = self.players.get(&msg.id).copied();
+        match self.apply(msg.id, msg.message, ctx) {
+            Ok(()) => {
+                self.send(msg.id, ServerMessage::Ack { request_id: msg.request_id });
+                let seat = self.players.get(&msg.id).copied();
+                if let Some(seat) = seat.filter(|_| seat != seat_before) {
+                    if let Some(token) = self.resume_token_for(seat) {
+                        let resume_token = token.clone();
+                        self.send(msg.id, ServerMessage::Seated { seat, resume_token });
+                    }
+                }
+                self.publish_events();
+                self.schedule_clock(ctx);
+                // Taking or leaving a seat changes which cards the session can
+                // see, and the stacks worth keeping
+                if seat != seat_before {
+                    self.send_state(msg.id);
+                    self.save_snapshot();
+                }
+            }
+            Err(error) => self.send(msg.id, error.reply(msg.request_id)),
+        }
+    }
+}
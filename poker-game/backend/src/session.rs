@@ -0,0 +1,179 @@
+use actix::{fut, Actor, ActorContext, ActorFutureExt, Addr, AsyncContext, ContextFutureSpawner, Handler, Running, StreamHandler, WrapFuture};
+use actix_web_actors::ws;
+use std::time::{Duration, Instant};
+
+use crate::protocol::{self, ClientMessage, ErrorCode, ProtocolError, RequestId, ServerMessage, PROTOCOL_VERSION};
+use crate::server::{CloseSession, Connect, Disconnect, SendText, SessionMessage, TableServer};
+
+/// How often the server pings clients, and how long it waits to hear
+/// back before giving up on the connection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Heartbeat {
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+impl Heartbeat {
+    /// Reads `HEARTBEAT_INTERVAL_SECS` and `HEARTBEAT_TIMEOUT_SECS`, falling
+    /// back to the defaults for anything unset or invalid.
+    pub fn from_env() -> Self {
+        let secs = |name: &str| {
+            std::env::var(name).ok()
+                .and_then(|value| value.parse().ok())
+                .filter(|&secs| secs > 0)
+                .map(Duration::from_secs)
+        };
+        let default = Heartbeat::default();
+        Heartbeat {
+            interval: secs("HEARTBEAT_INTERVAL_SECS").unwrap_or(default.interval),
+            timeout: secs("HEARTBEAT_TIMEOUT_SECS").unwrap_or(default.timeout),
+        }
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Heartbeat {
+            interval: Duration::from_secs(5),
+            timeout: Duration::from_secs(15),
+        }
+    }
+}
+
+/// A websocket connection to a table.
+pub struct WsSession {
+    /// Id assigned by the table server, once the handshake is done.
+    pub id: Option<usize>,
+    pub server: Addr<TableServer>,
+    pub heartbeat: Heartbeat,
+    /// When we last heard anything from the client.
+    last_heard: Instant,
+}
+
+impl WsSession {
+    pub fn new(server: Addr<TableServer>, heartbeat: Heartbeat) -> Self {
+        WsSession {
+            id: None,
+            server,
+            heartbeat,
+            last_heard: Instant::now(),
+        }
+    }
+
+    fn send(&self, ctx: &mut ws::WebsocketContext<Self>, message: ServerMessage) {
+        ctx.text(message.to_json());
+    }
+
+    /// Completes the handshake and subscribes to table updates if the client
+    /// speaks our protocol version.
+    fn hello(&mut self, version: u32, request_id: Option<RequestId>, ctx: &mut ws::WebsocketContext<Self>) {
+        if self.id.is_some() {
+            let error = ProtocolError::new(ErrorCode::InvalidMessage, "Already said hello");
+            self.send(ctx, error.reply(request_id));
+            return;
+        }
+        if version != PROTOCOL_VERSION {
+            let message = format!("Server speaks protocol version {}", PROTOCOL_VERSION);
+            let error = ProtocolError::new(ErrorCode::UnsupportedVersion, message);
+            self.send(ctx, error.reply(request_id));
+            ctx.close(None);
+            ctx.stop();
+            return;
+        }
+
+        let addr = ctx.address();
+        self.server
+            .send(Connect { addr })
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                match res {
+                    Ok(id) => {
+                        act.id = Some(id);
+                        act.send(ctx, ServerMessage::Welcome { version: PROTOCOL_VERSION, session_id: id });
+                    }
+                    _ => ctx.stop(),
+                }
+                fut::ready(())
+            })
+            .wait(ctx);
+    }
+}
+
+impl Actor for WsSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        // Ping the client regularly and drop it once it stops answering, so
+        // a dead connection's seat is marked disconnected
+        ctx.run_interval(self.heartbeat.interval, |act, ctx| {
+            if act.last_heard.elapsed() > act.heartbeat.timeout {
+                ctx.close(None);
+                ctx.stop();
+                return;
+            }
+            ctx.ping(b"");
+        });
+    }
+
+    fn stopping(&mut self, _: &mut Self::Context) -> Running {
+        if let Some(id) = self.id {
+            self.server.do_send(Disconnect { id });
+        }
+        Running::Stop
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WsSession {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        if msg.is_ok() {
+            self.last_heard = Instant::now();
+        }
+        match msg {
+            Ok(ws::Message::Text(text)) => {
+                // Handle incoming messages from clients
+                let frame = match protocol::parse_client_frame(&text) {
+                    Ok(frame) => frame,
+                    Err((request_id, error)) => {
+                        self.send(ctx, error.reply(request_id));
+                        return;
+                    }
+                };
+                match (frame.message, self.id) {
+                    (ClientMessage::Hello { version }, _) => self.hello(version, frame.request_id, ctx),
+                    (message, Some(id)) => {
+                        self.server.do_send(SessionMessage { id, request_id: frame.request_id, message });
+                    }
+                    (_, None) => {
+                        let error = ProtocolError::new(ErrorCode::HandshakeRequired, "Say hello first");
+                        self.send(ctx, error.reply(frame.request_id));
+                    }
+                }
+            }
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Handler<SendText> for WsSession {
+    type Result = ();
+
+    fn handle(&mut self, msg: SendText, ctx: &mut Self::Context) {
+        ctx.text(msg.0);
+    }
+}
+
+impl Handler<CloseSession> for WsSession {
+    type Result = ();
+
+    fn handle(&mut self, _: CloseSession, ctx: &mut Self::Context) {
+        // The table is gone, so there's nobody to tell we're leaving
+        self.id = None;
+        ctx.close(None);
+        ctx.stop();
+    }
+}
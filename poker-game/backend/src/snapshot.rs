@@ -0,0 +1,140 @@
+//! Table snapshots on disk, so a restart picks up where it left off.
+//!
+//! Each table is written to `<dir>/table-<id>.json`, replaced atomically
+//! every time, and the last table id handed out is kept in
+//! `<dir>/last-table-id` so ids are never reused. A hand in progress is never written: the snapshot is taken
+//! as if the hand were voided, so a crash mid-hand hands everyone's chips
+//! back as they were when it was dealt.
+
+use poker_core::Game;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::accounts::AccountId;
+use crate::lobby::TableId;
+use crate::table::TableSettings;
+
+/// Where snapshots are written unless `SNAPSHOT_DIR` says otherwise.
+const DEFAULT_DIR: &str = "snapshots";
+/// How often tables are snapshotted unless `SNAPSHOT_INTERVAL_SECS` says otherwise.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Everything needed to bring a table back after a restart.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TableSnapshot {
+    pub id: TableId,
+    pub settings: TableSettings,
+    /// The game between hands, with stacks, seats and the button.
+    pub game: Game,
+    /// Tokens seated players can reclaim their seats with, by token.
+    pub resume_tokens: HashMap<String, usize>,
+    /// Time bank seconds each player has left, by player.
+    pub time_banks: HashMap<usize, u64>,
+    /// Account each seat was bought in by, by player.
+    #[serde(default)]
+    pub seat_accounts: HashMap<usize, AccountId>,
+    /// Chips still to go back to players who left during the voided hand.
+    #[serde(default)]
+    pub owed: Vec<OwedCashOut>,
+}
+
+/// Chips a player took off the table that haven't reached their bankroll.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OwedCashOut {
+    pub account: AccountId,
+    pub chips: u32,
+}
+
+pub struct SnapshotStore {
+    dir: PathBuf,
+    /// How often each table is snapshotted, besides after every hand.
+    pub interval: Duration,
+}
+
+impl SnapshotStore {
+    pub fn open(dir: impl Into<PathBuf>, interval: Duration) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(SnapshotStore { dir, interval })
+    }
+
+    /// Opens the store in `SNAPSHOT_DIR`, snapshotting every
+    /// `SNAPSHOT_INTERVAL_SECS`, falling back to the defaults for anything
+    /// unset or invalid.
+    pub fn from_env() -> io::Result<Self> {
+        let dir = std::env::var("SNAPSHOT_DIR").unwrap_or_else(|_| DEFAULT_DIR.to_string());
+        let interval = std::env::var("SNAPSHOT_INTERVAL_SECS").ok()
+            .and_then(|value| value.parse().ok())
+            .filter(|&secs| secs > 0)
+            .map_or(DEFAULT_INTERVAL, Duration::from_secs);
+        SnapshotStore::open(dir, interval)
+    }
+
+    fn path(&self, id: TableId) -> PathBuf {
+        self.dir.join(format!("table-{}.json", id))
+    }
+
+    fn last_table_id_path(&self) -> PathBuf {
+        self.dir.join("last-table-id")
+    }
+
+    /// Writes the table's snapshot in place of the last one.
+    pub fn save(&self, snapshot: &TableSnapshot) -> io::Result<()> {
+        write_atomically(&self.path(snapshot.id), &serde_json::to_vec(snapshot)?)
+    }
+
+    /// The last table id handed out, open or closed.
+    pub fn last_table_id(&self) -> io::Result<Option<TableId>> {
+        match fs::read_to_string(self.last_table_id_path()) {
+            Ok(id) => id.trim().parse().map(Some).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Remembers that `id` has been handed out.
+    pub fn save_last_table_id(&self, id: TableId) -> io::Result<()> {
+        write_atomically(&self.last_table_id_path(), id.to_string().as_bytes())
+    }
+
+    /// Forgets a table that was closed on purpose.
+    pub fn remove(&self, id: TableId) -> io::Result<()> {
+        match fs::remove_file(self.path(id)) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    /// Every table snapshotted, in id order. Snapshots that can't be read
+    /// are reported and skipped rather than stopping the server.
+    pub fn load(&self) -> io::Result<Vec<TableSnapshot>> {
+        let mut snapshots = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let snapshot = fs::read(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|bytes| serde_json::from_slice::<TableSnapshot>(&bytes).map_err(|err| err.to_string()));
+            match snapshot {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(err) => eprintln!("Couldn't restore {}: {}", path.display(), err),
+            }
+        }
+        snapshots.sort_by_key(|snapshot| snapshot.id);
+        Ok(snapshots)
+    }
+}
+
+/// Replaces the file at `path` only once `contents` are safely on disk.
+fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
@@ -0,0 +1,256 @@
+use poker_core::fair::{self, Seed, SeedReveal};
+use poker_core::{BettingStructure, Blinds, Game, GameEvent, GameState, PlayerView};
+use serde::{Deserialize, Serialize};
+
+/// Longest client seed accepted, to keep a hand's seed list small.
+const MAX_CLIENT_SEED_LEN: usize = 128;
+
+/// Server side of the commit-reveal shuffle for one table.
+///
+/// The seed for the next hand is generated as soon as the previous one is
+/// dealt, so its commitment is public before anyone contributes a client seed.
+pub struct FairShuffle {
+    next_seed: Seed,
+    /// Seeds for the next hand in the order received, with the seat each
+    /// came from.
+    next_client_seeds: Vec<(usize, String)>,
+    /// Seeds the hand in progress was dealt from, kept secret until it ends.
+    in_play: Option<SeedReveal>,
+    pub last_reveal: Option<SeedReveal>,
+}
+
+impl FairShuffle {
+    pub fn new() -> Self {
+        FairShuffle {
+            next_seed: fair::generate_server_seed(),
+            next_client_seeds: vec![],
+            in_play: None,
+            last_reveal: None,
+        }
+    }
+
+    /// Commitment to the server seed that will shuffle the next hand.
+    pub fn commitment(&self) -> String {
+        fair::commit(&self.next_seed)
+    }
+
+    /// Adds `player`'s seed to the next hand's shuffle, replacing any they
+    /// sent before. Returns false if the seed was rejected.
+    pub fn add_client_seed(&mut self, player: usize, seed: String) -> bool {
+        if seed.is_empty() || seed.len() > MAX_CLIENT_SEED_LEN {
+            return false;
+        }
+        match self.next_client_seeds.iter_mut().find(|(p, _)| *p == player) {
+            Some((_, previous)) => *previous = seed,
+            None => self.next_client_seeds.push((player, seed)),
+        }
+        true
+    }
+
+    /// Builds the deck for hand `hand_number` and commits to a fresh seed
+    /// for the hand after it.
+    fn start_hand(&mut self, hand_number: u32) -> poker_core::Deck {
+        let server_seed = std::mem::replace(&mut self.next_seed, fair::generate_server_seed());
+        let client_seeds: Vec<String> = std::mem::take(&mut self.next_client_seeds)
+            .into_iter()
+            .map(|(_, seed)| seed)
+            .collect();
+        let deck = fair::shuffled_deck(&fair::combine_seeds(&server_seed, &client_seeds));
+        self.in_play = Some(SeedReveal::new(hand_number, &server_seed, client_seeds));
+        deck
+    }
+}
+
+impl Default for FairShuffle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where the clock stands for the player to act.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ClockState {
+    pub player: usize,
+    pub seconds_left: u64,
+    /// Whether the player is now drawing on their time bank.
+    pub in_time_bank: bool,
+}
+
+/// Something that happened at the table: a game event or a change to the
+/// server-side state around it.
+#[derive(Serialize, Debug, Clone)]
+pub enum TableEvent {
+    ShuffleCommitted { commitment: String },
+    ShuffleRevealed(SeedReveal),
+    PlayerDisconnected { player: usize },
+    PlayerReconnected { player: usize },
+    /// The player didn't come back within the grace period.
+    ReconnectExpired { player: usize },
+    /// Time left for the player to act.
+    ActionClock(ClockState),
+    #[serde(untagged)]
+    Game(GameEvent),
+}
+
+impl TableEvent {
+    /// The event as `viewer` may see it.
+    pub fn visible_to(&self, viewer: Option<usize>) -> TableEvent {
+        match self {
+            TableEvent::Game(event) => TableEvent::Game(event.visible_to(viewer)),
+            event => event.clone(),
+        }
+    }
+}
+
+/// How a table is set up when it's created.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TableSettings {
+    pub name: String,
+    pub blinds: Blinds,
+    /// No-limit unless set.
+    pub betting_structure: BettingStructure,
+    pub max_seats: usize,
+    pub min_buy_in: u32,
+    pub max_buy_in: u32,
+    /// Seconds the player to act has to decide, or 0 for no clock.
+    pub action_time_secs: u64,
+    /// Extra seconds each player can draw on over the whole session once
+    /// their action time runs out, or 0 for none.
+    pub time_bank_secs: u64,
+    /// How long a dropped player's seat is held for them to reconnect.
+    pub reconnect_grace_secs: u64,
+    /// How long to wait for a disconnected player before checking or folding.
+    pub disconnected_time_bank_secs: u64,
+}
+
+impl TableSettings {
+    /// Checks the settings make a playable table, returning why not if they don't.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Table name can't be empty".to_string());
+        }
+        if self.blinds.big_blind == 0 || self.blinds.small_blind > self.blinds.big_blind {
+            return Err("Big blind must be positive and at least the small blind".to_string());
+        }
+        if let BettingStructure::FixedLimit { small_bet, big_bet, raise_cap } = self.betting_structure {
+            if small_bet == 0 || big_bet < small_bet || raise_cap == 0 {
+                return Err("Fixed limit needs a positive small bet, a big bet at least as large and a raise cap".to_string());
+            }
+        }
+        if !(2..=10).contains(&self.max_seats) {
+            return Err("Tables seat between 2 and 10 players".to_string());
+        }
+        if self.min_buy_in < self.blinds.big_blind || self.min_buy_in > self.max_buy_in {
+            return Err("Minimum buy-in must be at least the big blind and no more than the maximum".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn allows_buy_in(&self, chips: u32) -> bool {
+        (self.min_buy_in..=self.max_buy_in).contains(&chips)
+    }
+}
+
+impl Default for TableSettings {
+    fn default() -> Self {
+        TableSettings {
+            name: "Table".to_string(),
+            blinds: Blinds::default(),
+            betting_structure: BettingStructure::NoLimit,
+            max_seats: 9,
+            min_buy_in: 200,
+            max_buy_in: 1000,
+            action_time_secs: 30,
+            time_bank_secs: 60,
+            reconnect_grace_secs: 60,
+            disconnected_time_bank_secs: 10,
+        }
+    }
+}
+
+/// A game plus the server-only state that goes with it.
+pub struct Table {
+    pub settings: TableSettings,
+    pub game: Game,
+    pub shuffle: FairShuffle,
+    /// Events recorded since they were last drained, in the order they happened.
+    events: Vec<TableEvent>,
+}
+
+/// What a client is sent after each change: their view of the game with the
+/// shuffle commitment for the next hand and the reveal for the last one.
+/// Spectators get the reveal too, so anyone can rebuild the deck, folded
+/// hands and all, and check the deal.
+#[derive(Serialize)]
+pub struct TableState<'a> {
+    #[serde(flatten)]
+    pub view: PlayerView,
+    pub shuffle_commitment: String,
+    pub last_shuffle: Option<&'a SeedReveal>,
+}
+
+impl Table {
+    pub fn new(settings: TableSettings) -> Self {
+        let mut game = Game::with_blinds(settings.blinds.clone());
+        game.betting_structure = settings.betting_structure.clone();
+        Table {
+            game,
+            settings,
+            shuffle: FairShuffle::new(),
+            events: vec![],
+        }
+    }
+
+    /// Records a table event after any game events that came before it.
+    pub fn record(&mut self, event: TableEvent) {
+        self.events.extend(self.game.drain_events().into_iter().map(TableEvent::Game));
+        self.events.push(event);
+    }
+
+    /// Takes every event since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<TableEvent> {
+        self.events.extend(self.game.drain_events().into_iter().map(TableEvent::Game));
+        std::mem::take(&mut self.events)
+    }
+
+    /// Deals a new hand from the committed shuffle, unless one is already
+    /// being played or there aren't enough players.
+    pub fn deal(&mut self) -> bool {
+        if self.game.state == GameState::PlayerTurn || !self.game.can_deal() {
+            return false;
+        }
+        let deck = self.shuffle.start_hand(self.game.hand_number + 1);
+        self.game.deal_cards_from(deck);
+        self.record(TableEvent::ShuffleCommitted { commitment: self.shuffle.commitment() });
+        self.finish_hand_if_over();
+        true
+    }
+
+    /// Reveals the shuffle seeds once the hand in progress has finished.
+    pub fn finish_hand_if_over(&mut self) {
+        let hand_over = matches!(self.game.state, GameState::ShowDown | GameState::GameOver);
+        if !hand_over {
+            return;
+        }
+        if let Some(reveal) = self.shuffle.in_play.take() {
+            self.record(TableEvent::ShuffleRevealed(reveal.clone()));
+            self.shuffle.last_reveal = Some(reveal);
+        }
+    }
+
+    /// The table as seen from `viewer`'s seat, or by a spectator if `None`.
+    pub fn state(&self, viewer: Option<usize>) -> TableState<'_> {
+        TableState {
+            view: self.game.view_for(viewer),
+            shuffle_commitment: self.shuffle.commitment(),
+            last_shuffle: self.shuffle.last_reveal.as_ref(),
+        }
+    }
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Self::new(TableSettings::default())
+    }
+}
@@ -4,20 +4,121 @@ document.addEventListener('DOMContentLoaded', () => {
     const canvas = document.getElementById('game-canvas');
     const ctx = canvas.getContext('2d');
 
-    // Establish WebSocket connection to the backend
-    const socket = new WebSocket('ws://127.0.0.1:8080/ws');
+    // Establish WebSocket connection to a table, the first one by default
+    const tableId = new URLSearchParams(location.search).get('table') || 1;
+    const socket = new WebSocket(`ws://127.0.0.1:8080/ws/${tableId}`);
+
+    const PROTOCOL_VERSION = 2;
+    let nextRequestId = 1;
+    // Latest snapshot, kept up to date by applying events to it
+    let table = null;
+
+    function send(type, fields = {}) {
+        socket.send(JSON.stringify({ type, request_id: nextRequestId++, ...fields }));
+    }
 
     socket.addEventListener('open', () => {
         console.log('Connected to the backend');
-        // Send initial message to join the game
-        socket.send(JSON.stringify("Join"));
+        send('hello', { version: PROTOCOL_VERSION });
     });
 
+    const resumeKey = `resume-token-${tableId}`;
+    // Our account is the same at every table, so it outlives the tab
+    const accountKey = 'account-token';
+
+    function register() {
+        // Open an account under a chosen name
+        const name = prompt('Choose a display name', 'Player') || 'Player';
+        send('register', { name });
+    }
+
+    function join() {
+        // Buy in from our bankroll, opening an account first if we have none
+        const accountToken = localStorage.getItem(accountKey);
+        if (accountToken) {
+            send('join', { account_token: accountToken });
+        } else {
+            register();
+        }
+    }
+
+    function randomSeed() {
+        const bytes = new Uint8Array(16);
+        crypto.getRandomValues(bytes);
+        return Array.from(bytes, (b) => b.toString(16).padStart(2, '0')).join('');
+    }
+
+    function contributeSeed() {
+        // Only seated players' seeds count, one each for the next shuffle
+        if (sessionStorage.getItem(resumeKey)) {
+            send('client_seed', { seed: randomSeed() });
+        }
+    }
+
     socket.addEventListener('message', (event) => {
         console.log('Message from server:', event.data);
         const data = JSON.parse(event.data);
-        // Handle game state updates here
-        drawGameBoard(data);
+        switch (data.type) {
+            case 'welcome': {
+                // Take back our seat if we were disconnected, otherwise join
+                const token = sessionStorage.getItem(resumeKey);
+                if (token) {
+                    send('resume', { token });
+                } else {
+                    join();
+                }
+                break;
+            }
+            case 'account':
+                console.log(`Opened account ${data.id} with ${data.balance} chips`);
+                localStorage.setItem(accountKey, data.token);
+                join();
+                break;
+            case 'seated':
+                sessionStorage.setItem(resumeKey, data.resume_token);
+                // Contribute our own randomness to the next shuffle
+                contributeSeed();
+                break;
+            case 'error':
+                if (data.code === 'invalid_resume_token') {
+                    // Our seat has gone, so start again with a new one
+                    sessionStorage.removeItem(resumeKey);
+                    join();
+                    break;
+                }
+                if (data.code === 'invalid_account_token') {
+                    // The account we remembered is unknown here, so open another
+                    localStorage.removeItem(accountKey);
+                    register();
+                    break;
+                }
+                if (data.code === 'name_taken' || data.code === 'invalid_name') {
+                    alert(data.message);
+                    register();
+                    break;
+                }
+                // The server rejected one of our requests
+                console.warn(`Request ${data.request_id} failed (${data.code}):`, data.message);
+                break;
+            case 'table_closed':
+                console.log('The table was closed');
+                break;
+            case 'state':
+                table = data;
+                drawGameBoard(table);
+                break;
+            case 'event':
+                if (table && data.seq === table.seq + 1) {
+                    applyEvent(table, data.event);
+                    table.seq = data.seq;
+                    drawGameBoard(table);
+                }
+                // Each hand's shuffle takes fresh randomness
+                if ('ShuffleCommitted' in data.event) {
+                    contributeSeed();
+                }
+                break;
+        }
     });
 
     socket.addEventListener('close', () => {
@@ -28,6 +129,50 @@ document.addEventListener('DOMContentLoaded', () => {
         console.error('WebSocket error:', error);
     });
 
+    // Updates the fields we draw from a single event
+    function applyEvent(state, event) {
+        const [kind, body] = Object.entries(event)[0];
+        // What we could do no longer holds once the hand moves on; the server
+        // sends a fresh state when it's our turn again
+        if (['HandStarted', 'PlayerActed', 'StreetDealt', 'PotAwarded'].includes(kind)) {
+            state.legal_actions = [];
+        }
+        switch (kind) {
+            case 'HandStarted':
+                state.pot = 0;
+                state.current_bet = 0;
+                state.community_cards = [];
+                break;
+            case 'BlindPosted':
+                state.pot += body.amount;
+                if (body.kind !== 'Ante') {
+                    state.current_bet = Math.max(state.current_bet, body.amount);
+                }
+                break;
+            case 'PlayerActed':
+                state.action_clock = null;
+                state.pot += body.amount;
+                state.current_bet = Math.max(state.current_bet, body.total);
+                break;
+            case 'StreetDealt':
+                state.current_bet = 0;
+                state.community_cards.push(...body.cards);
+                break;
+            case 'PotAwarded':
+                state.pot = Math.max(0, state.pot - body.amount);
+                break;
+            case 'ActionClock':
+                state.action_clock = body;
+                break;
+            case 'ShuffleCommitted':
+                state.shuffle_commitment = body.commitment;
+                break;
+            case 'ShuffleRevealed':
+                state.last_shuffle = body;
+                break;
+        }
+    }
+
     function drawGameBoard(gameState) {
         // Clear the canvas
         ctx.clearRect(0, 0, canvas.width, canvas.height);
@@ -47,22 +192,86 @@ document.addEventListener('DOMContentLoaded', () => {
         // Draw pot and current bet
         ctx.fillText(`Pot: ${gameState.pot}`, 20, canvas.height - 30);
         ctx.fillText(`Current Bet: ${gameState.current_bet}`, 20, canvas.height - 10);
+
+        // Draw the clock on the player to act
+        if (gameState.action_clock) {
+            const clock = gameState.action_clock;
+            const bank = clock.in_time_bank ? ' (time bank)' : '';
+            ctx.fillText(`Player ${clock.player} to act: ${clock.seconds_left}s${bank}`, 20, 130);
+        }
+
+        // Draw the shuffle commitment for the next hand and the last reveal
+        ctx.font = '12px monospace';
+        ctx.fillText(`Next shuffle commitment: ${gameState.shuffle_commitment}`, 20, canvas.height - 70);
+        if (gameState.last_shuffle) {
+            ctx.fillText(`Hand ${gameState.last_shuffle.hand_number} server seed: ${gameState.last_shuffle.server_seed}`, 20, canvas.height - 55);
+        }
+    }
+
+    // The bounds of one of our legal actions, or null if we can't take it now
+    function legalAction(...kinds) {
+        for (const action of (table && table.legal_actions) || []) {
+            // Actions without bounds are sent as bare names
+            const [kind, bounds] = typeof action === 'string' ? [action, {}] : Object.entries(action)[0];
+            if (kinds.includes(kind)) {
+                return bounds;
+            }
+        }
+        return null;
+    }
+
+    function bet() {
+        // Bet or raise to an amount within the legal range
+        const { min, max } = legalAction('Bet', 'Raise');
+        const amount = parseInt(prompt(`Bet or raise to (${min} to ${max})`, min), 10);
+        if (amount >= min && amount <= max) {
+            send('bet', { amount });
+        }
     }
 
-    // Add event listeners for game actions
+    // Add event listeners for game actions, ignoring any we can't take
     document.addEventListener('keydown', (event) => {
         switch (event.key) {
             case 'b':
-                socket.send(JSON.stringify({ Bet: 10 }));
+                if (legalAction('Bet', 'Raise')) {
+                    bet();
+                }
                 break;
             case 'f':
-                socket.send(JSON.stringify("Fold"));
+                if (legalAction('Fold')) {
+                    send('fold');
+                }
                 break;
             case 'c':
-                socket.send(JSON.stringify("Check"));
+                if (legalAction('Check')) {
+                    send('check');
+                }
                 break;
             case 'a':
-                socket.send(JSON.stringify("Call"));
+                if (legalAction('Call')) {
+                    send('call');
+                }
+                break;
+            case 'v': {
+                // Push our whole stack in, if the betting allows it
+                const allIn = legalAction('AllIn');
+                if (allIn && confirm(`Go all-in for ${allIn.amount}?`)) {
+                    send('all_in');
+                }
+                break;
+            }
+            case 'd':
+                send('deal');
+                break;
+            case 's':
+                send('sit_out');
+                break;
+            case 'i':
+                send('sit_in');
+                break;
+            case 'l':
+                send('leave_table');
+                sessionStorage.removeItem(resumeKey);
                 break;
         }
     });
@@ -1,7 +1,8 @@
 use macroquad::prelude::*;
 
-mod game;
-use game::{Game, GameState, Round, Card, Rank};
+use poker_core::{Game, GameState, LegalAction, Round, Card, SeatStatus};
+
+mod replay;
 
 const WINDOW_WIDTH: f32 = 800.0;
 const WINDOW_HEIGHT: f32 = 600.0;
@@ -12,6 +13,7 @@ const CARD_COLOR: Color = WHITE;
 const TEXT_COLOR: Color = WHITE;
 const BUTTON_COLOR: Color = Color::new(0.2, 0.2, 0.8, 1.0);
 const BUTTON_HOVER_COLOR: Color = Color::new(0.3, 0.3, 0.9, 1.0);
+const BUTTON_DISABLED_COLOR: Color = Color::new(0.4, 0.4, 0.4, 1.0);
 const BUTTON_TEXT_COLOR: Color = WHITE;
 
 struct Button {
@@ -20,13 +22,22 @@ struct Button {
     action: ButtonAction,
 }
 
+#[derive(PartialEq)]
 enum ButtonAction {
     Bet,
     Call,
     Check,
     Fold,
+    AllIn,
     Deal,
     NewGame,
+    StepBack,
+    StepForward,
+    PlayPause,
+    Slower,
+    Faster,
+    PrevHand,
+    NextHand,
 }
 
 impl Button {
@@ -38,8 +49,14 @@ impl Button {
         }
     }
 
-    fn draw(&self, hovered: bool) {
-        let color = if hovered { BUTTON_HOVER_COLOR } else { BUTTON_COLOR };
+    fn draw(&self, hovered: bool, enabled: bool) {
+        let color = if !enabled {
+            BUTTON_DISABLED_COLOR
+        } else if hovered {
+            BUTTON_HOVER_COLOR
+        } else {
+            BUTTON_COLOR
+        };
         
         draw_rectangle(self.rect.x, self.rect.y, self.rect.w, self.rect.h, color);
         draw_rectangle_lines(self.rect.x, self.rect.y, self.rect.w, self.rect.h, 2.0, BLACK);
@@ -55,10 +72,34 @@ impl Button {
     fn is_hovered(&self, mouse_position: Vec2) -> bool {
         self.rect.contains(mouse_position)
     }
+
+    /// Betting buttons are only enabled for actions the player to act can take.
+    fn is_enabled(&self, legal_actions: &[LegalAction]) -> bool {
+        if !matches!(self.action, ButtonAction::Bet | ButtonAction::Call | ButtonAction::Check | ButtonAction::Fold | ButtonAction::AllIn) {
+            return true;
+        }
+        legal_actions.iter().any(|action| matches!(
+            (&self.action, action),
+            (ButtonAction::Bet, LegalAction::Bet { .. } | LegalAction::Raise { .. })
+                | (ButtonAction::Call, LegalAction::Call { .. })
+                | (ButtonAction::Check, LegalAction::Check)
+                | (ButtonAction::Fold, LegalAction::Fold)
+                | (ButtonAction::AllIn, LegalAction::AllIn { .. })
+        ))
+    }
 }
 
 #[macroquad::main("Poker Game")]
 async fn main() {
+    // Given a hand history file, replay it instead of playing
+    if let Some(path) = std::env::args().nth(1) {
+        match replay::Replay::load(&path) {
+            Ok(replay) => replay::run(replay).await,
+            Err(error) => eprintln!("{}", error),
+        }
+        return;
+    }
+    
     // Initialize game
     let mut game = Game::new();
     
@@ -72,52 +113,61 @@ async fn main() {
         Button::new(160.0, WINDOW_HEIGHT - 60.0, 100.0, 40.0, "Call", ButtonAction::Call),
         Button::new(270.0, WINDOW_HEIGHT - 60.0, 100.0, 40.0, "Check", ButtonAction::Check),
         Button::new(380.0, WINDOW_HEIGHT - 60.0, 100.0, 40.0, "Fold", ButtonAction::Fold),
+        Button::new(490.0, WINDOW_HEIGHT - 60.0, 100.0, 40.0, "All-in", ButtonAction::AllIn),
         Button::new(WINDOW_WIDTH - 150.0, WINDOW_HEIGHT - 60.0, 100.0, 40.0, "Deal", ButtonAction::Deal),
     ];
     
+    // Reason the last action was rejected, shown until the next valid action
+    let mut action_error: Option<String> = None;
+    
     // Game loop
     loop {
         // Process input
         let mouse_position = Vec2::new(mouse_position().0, mouse_position().1);
         
+        let legal_actions = game.legal_actions(game.current_player_index);
+        
         // Handle button interactions
         for button in &buttons {
-            if button.is_hovered(mouse_position) && is_mouse_button_pressed(MouseButton::Left) {
-                match button.action {
+            if button.is_hovered(mouse_position) && button.is_enabled(&legal_actions) && is_mouse_button_pressed(MouseButton::Left) {
+                let player = game.current_player_index;
+                let result = match button.action {
                     ButtonAction::Bet => {
-                        if game.state == GameState::PlayerTurn {
-                            game.player_bet(10);
-                        }
-                    },
-                    ButtonAction::Call => {
-                        if game.state == GameState::PlayerTurn {
-                            game.player_call();
-                        }
-                    },
-                    ButtonAction::Check => {
-                        if game.state == GameState::PlayerTurn {
-                            game.player_check();
-                        }
-                    },
-                    ButtonAction::Fold => {
-                        if game.state == GameState::PlayerTurn {
-                            game.player_fold();
-                        }
+                        // Bet or raise the minimum
+                        let min_raise = legal_actions.iter().find_map(|action| match action {
+                            LegalAction::Bet { min, .. } | LegalAction::Raise { min, .. } => Some(*min),
+                            _ => None,
+                        });
+                        min_raise.map(|amount| game.player_bet(player, amount))
                     },
+                    ButtonAction::Call => Some(game.player_call(player)),
+                    ButtonAction::Check => Some(game.player_check(player)),
+                    ButtonAction::Fold => Some(game.player_fold(player)),
+                    ButtonAction::AllIn => Some(game.player_all_in(player)),
                     ButtonAction::Deal => {
-                        if game.state == GameState::Setup || game.state == GameState::GameOver {
+                        if game.state == GameState::Setup || game.state == GameState::ShowDown || game.state == GameState::GameOver {
                             game.deal_cards();
                         }
+                        None
                     },
                     ButtonAction::NewGame => {
                         game = Game::new();
                         game.add_player("Player 1".to_string(), 1000);
                         game.add_player("Player 2".to_string(), 1000);
+                        None
                     },
+                    // Replay controls aren't shown in a live game
+                    _ => None,
+                };
+                if let Some(result) = result {
+                    action_error = result.err().map(|error| error.to_string());
                 }
             }
         }
         
+        // Nothing here reads the event stream, so don't let it pile up
+        game.drain_events();
+
         // Update game state
         if game.state == GameState::GameOver {
             // Replace Deal button with New Game button if not already done
@@ -134,81 +184,150 @@ async fn main() {
         }
         
         // Render
-        clear_background(DARKGRAY);
+        draw_table(&game);
         
-        // Draw poker table
-        draw_rectangle(50.0, 50.0, WINDOW_WIDTH - 100.0, WINDOW_HEIGHT - 150.0, TABLE_COLOR);
-        
-        // Draw community cards
-        let card_width = 70.0;
-        let card_height = 100.0;
-        let card_spacing = 10.0;
-        let start_x = (WINDOW_WIDTH - (5.0 * card_width + 4.0 * card_spacing)) / 2.0;
-        let community_y = 150.0;
-        
-        for (i, card) in game.community_cards.iter().enumerate() {
-            let x = start_x + i as f32 * (card_width + card_spacing);
-            draw_card(card, x, community_y, card_width, card_height);
+        // Draw the reason the last action was rejected
+        if let Some(error) = &action_error {
+            draw_text(error, 60.0, WINDOW_HEIGHT - 75.0, 20.0, RED);
         }
         
-        // Draw player hands
-        for (i, player) in game.players.iter().enumerate() {
-            let player_y = WINDOW_HEIGHT - 200.0 - i as f32 * 120.0;
-            
-            // Draw player info
-            draw_text(&format!("{}: ${}", player.name, player.chips), 60.0, player_y - 10.0, 20.0, TEXT_COLOR);
-            
-            // Draw player cards
-            for (j, card) in player.hand.iter().enumerate() {
-                let x = 60.0 + j as f32 * (card_width + card_spacing);
-                draw_card(card, x, player_y, card_width, card_height);
-            }
-            
-            // Highlight current player
-            if i == game.current_player_index && game.state == GameState::PlayerTurn {
-                draw_rectangle_lines(50.0, player_y - 30.0, 200.0, 120.0, 2.0, YELLOW);
-            }
+        // Draw buttons
+        let legal_actions = game.legal_actions(game.current_player_index);
+        for button in &buttons {
+            button.draw(button.is_hovered(mouse_position), button.is_enabled(&legal_actions));
         }
         
-        // Draw game info
-        draw_text(&format!("Pot: ${}", game.pot), WINDOW_WIDTH - 200.0, 80.0, 24.0, TEXT_COLOR);
-        draw_text(&format!("Current Bet: ${}", game.current_bet), WINDOW_WIDTH - 200.0, 110.0, 20.0, TEXT_COLOR);
-        
-        // Draw round info
-        let round_text = match game.round {
-            Round::PreFlop => "Pre-Flop",
-            Round::Flop => "Flop",
-            Round::Turn => "Turn",
-            Round::River => "River",
-            Round::Complete => "Complete",
-        };
-        draw_text(&format!("Round: {}", round_text), WINDOW_WIDTH - 200.0, 140.0, 20.0, TEXT_COLOR);
-        
-        // Draw game state
-        let state_text = match game.state {
-            GameState::Setup => "Setup",
-            GameState::Dealing => "Dealing",
-            GameState::Betting => "Betting",
-            GameState::PlayerTurn => "Player Turn",
-            GameState::ShowDown => "Show Down",
-            GameState::GameOver => "Game Over",
-        };
-        draw_text(&format!("State: {}", state_text), WINDOW_WIDTH - 200.0, 170.0, 20.0, TEXT_COLOR);
+        next_frame().await;
+    }
+}
+
+/// Draws the table, cards, players and pots for `game`.
+fn draw_table(game: &Game) {
+    clear_background(DARKGRAY);
+    
+    // Draw poker table
+    draw_rectangle(50.0, 50.0, WINDOW_WIDTH - 100.0, WINDOW_HEIGHT - 150.0, TABLE_COLOR);
+    
+    // Draw community cards
+    let card_width = 70.0;
+    let card_height = 100.0;
+    let card_spacing = 10.0;
+    let start_x = (WINDOW_WIDTH - (5.0 * card_width + 4.0 * card_spacing)) / 2.0;
+    let community_y = 150.0;
+    
+    for (i, card) in game.community_cards.iter().enumerate() {
+        let x = start_x + i as f32 * (card_width + card_spacing);
+        draw_card(card, x, community_y, card_width, card_height);
+    }
+    
+    // Draw player hands, skipping empty seats
+    let seated = game.players.iter()
+        .enumerate()
+        .filter(|(_, player)| player.status != SeatStatus::Empty);
+    for (row, (i, player)) in seated.enumerate() {
+        let player_y = WINDOW_HEIGHT - 200.0 - row as f32 * 120.0;
         
-        // Draw winner if game is over
-        if game.state == GameState::GameOver {
-            if let Some(winner_index) = game.get_winner() {
-                let winner = &game.players[winner_index];
-                draw_text(&format!("Winner: {}", winner.name), WINDOW_WIDTH / 2.0 - 100.0, 100.0, 30.0, GOLD);
-            }
+        // Draw player info
+        let status = if player.is_all_in { " (All-in)" } else { "" };
+        draw_text(format!("{}{}: ${}{}", player.name, position_label(game, i), player.chips, status), 60.0, player_y - 10.0, 20.0, TEXT_COLOR);
+        if player.street_contribution > 0 {
+            draw_text(format!("Bet: ${}", player.street_contribution), 220.0, player_y - 10.0, 20.0, TEXT_COLOR);
         }
         
-        // Draw buttons
-        for button in &buttons {
-            button.draw(button.is_hovered(mouse_position));
+        // Draw player cards
+        for (j, card) in player.hand.iter().enumerate() {
+            let x = 60.0 + j as f32 * (card_width + card_spacing);
+            draw_card(card, x, player_y, card_width, card_height);
         }
         
-        next_frame().await;
+        // Highlight current player
+        if i == game.current_player_index && game.state == GameState::PlayerTurn {
+            draw_rectangle_lines(50.0, player_y - 30.0, 200.0, 120.0, 2.0, YELLOW);
+        }
+    }
+    
+    // Draw game info
+    draw_text(format!("Pot: ${}", game.pot), WINDOW_WIDTH - 200.0, 80.0, 24.0, TEXT_COLOR);
+    draw_text(format!("Current Bet: ${}", game.current_bet), WINDOW_WIDTH - 200.0, 110.0, 20.0, TEXT_COLOR);
+    draw_text(format!("Blinds: ${}/${}", game.blinds.small_blind, game.blinds.big_blind), WINDOW_WIDTH - 200.0, 190.0, 20.0, TEXT_COLOR);
+    if game.state == GameState::PlayerTurn
+        && let Some((min_raise, max_raise)) = game.raise_bounds()
+    {
+        draw_text(format!("Raise to: ${}-${}", min_raise, max_raise), WINDOW_WIDTH - 200.0, 210.0, 18.0, TEXT_COLOR);
+    }
+    
+    // Draw round info
+    let round_text = match game.round {
+        Round::PreFlop => "Pre-Flop",
+        Round::Flop => "Flop",
+        Round::Turn => "Turn",
+        Round::River => "River",
+        Round::Complete => "Complete",
+    };
+    draw_text(format!("Round: {}", round_text), WINDOW_WIDTH - 200.0, 140.0, 20.0, TEXT_COLOR);
+    
+    // Draw game state
+    let state_text = match game.state {
+        GameState::Setup => "Setup",
+        GameState::Dealing => "Dealing",
+        GameState::Betting => "Betting",
+        GameState::PlayerTurn => "Player Turn",
+        GameState::ShowDown => "Show Down",
+        GameState::GameOver => "Game Over",
+    };
+    draw_text(format!("State: {}", state_text), WINDOW_WIDTH - 200.0, 170.0, 20.0, TEXT_COLOR);
+    
+    // Draw side pots while the hand is being played
+    let pots = game.pots();
+    if pots.len() > 1 && game.state == GameState::PlayerTurn {
+        for (i, pot) in pots.iter().enumerate() {
+            draw_text(format!("{}: ${}", pot_name(i), pot.amount), WINDOW_WIDTH - 200.0, 235.0 + i as f32 * 20.0, 18.0, TEXT_COLOR);
+        }
+    }
+    
+    // Draw who won each pot at showdown or once the game is over
+    if game.state == GameState::ShowDown || game.state == GameState::GameOver {
+        for (i, award) in game.pot_awards.iter().enumerate() {
+            let names: Vec<&str> = award.winners.iter().map(|&w| game.players[w].name.as_str()).collect();
+            let mut award_text = format!("{} ${}: {}", pot_name(award.pot_index), award.amount, names.join(", "));
+            if game.state == GameState::ShowDown
+                && let Some(strength) = award.winners.first().and_then(|&w| game.hand_strength(w))
+            {
+                award_text = format!("{} ({})", award_text, strength);
+            }
+            draw_text(award_text, WINDOW_WIDTH / 2.0 - 200.0, 100.0 + i as f32 * 30.0, 24.0, GOLD);
+        }
+    }
+}
+
+fn position_label(game: &Game, player_index: usize) -> String {
+    if game.hand_number == 0 {
+        return String::new();
+    }
+    
+    let mut positions = vec![];
+    if player_index == game.dealer_index {
+        positions.push("D");
+    }
+    if player_index == game.small_blind_index {
+        positions.push("SB");
+    }
+    if player_index == game.big_blind_index {
+        positions.push("BB");
+    }
+    
+    if positions.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", positions.join("/"))
+    }
+}
+
+fn pot_name(pot_index: usize) -> String {
+    if pot_index == 0 {
+        "Main pot".to_string()
+    } else {
+        format!("Side pot {}", pot_index)
     }
 }
 
@@ -224,16 +343,8 @@ fn draw_card(card: &Card, x: f32, y: f32, width: f32, height: f32) {
     };
     
     // Draw card rank
-    let rank_str = match card.rank {
-        Rank::Number(n) => n.to_string(),
-        Rank::Jack => "J".to_string(),
-        Rank::Queen => "Q".to_string(),
-        Rank::King => "K".to_string(),
-        Rank::Ace => "A".to_string(),
-    };
-    
-    draw_text(&rank_str, x + 5.0, y + 20.0, 20.0, color);
+    draw_text(card.rank.to_string(), x + 5.0, y + 20.0, 20.0, color);
     
     // Draw card suit
-    draw_text(&card.suit.to_string(), x + 5.0, y + 45.0, 30.0, color);
+    draw_text(card.suit.to_string(), x + 5.0, y + 45.0, 30.0, color);
 }
@@ -0,0 +1,250 @@
+//! Steps through recorded hands, drawing the table the same way as a live game.
+
+use macroquad::prelude::*;
+use serde_json::Value;
+
+use poker_core::{BlindKind, Game, HandActionKind, HandHistory, PlayerAction};
+
+use crate::{draw_table, Button, ButtonAction, TEXT_COLOR, WINDOW_HEIGHT};
+
+/// Seconds between steps when playing at normal speed.
+const STEP_SECS: f32 = 1.0;
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 8.0;
+
+pub struct Replay {
+    hands: Vec<HandHistory>,
+    hand: usize,
+    /// The table after the deal and after each action of the current hand.
+    frames: Vec<Game>,
+    /// What led to each frame, for the caption.
+    captions: Vec<String>,
+    step: usize,
+    playing: bool,
+    speed: f32,
+    /// Seconds since the last step while playing.
+    elapsed: f32,
+    /// Why the current hand couldn't be replayed past its last frame.
+    error: Option<String>,
+}
+
+impl Replay {
+    /// Loads every hand in a history file. The file can hold a single hand,
+    /// or one per line as the backend archives them, either bare or wrapped
+    /// in the archive's record. Lines that aren't hands are skipped.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+        let hands: Vec<HandHistory> = match serde_json::from_str::<Value>(&text) {
+            Ok(value) => hand_from(value).into_iter().collect(),
+            Err(_) => text.lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .filter_map(hand_from)
+                .collect(),
+        };
+        if hands.is_empty() {
+            return Err(format!("No hand histories in {}", path));
+        }
+
+        let mut replay = Replay {
+            hands,
+            hand: 0,
+            frames: vec![],
+            captions: vec![],
+            step: 0,
+            playing: false,
+            speed: 1.0,
+            elapsed: 0.0,
+            error: None,
+        };
+        replay.load_hand(0);
+        Ok(replay)
+    }
+
+    fn load_hand(&mut self, hand: usize) {
+        let history = &self.hands[hand];
+        let (frames, error) = match history.replay() {
+            Ok(frames) => (frames, None),
+            Err(err) => {
+                // Show as far as it got
+                let mut partial = history.clone();
+                partial.actions.truncate(err.action);
+                (partial.replay().unwrap_or_default(), Some(err.to_string()))
+            }
+        };
+        self.hand = hand;
+        self.frames = frames;
+        self.captions = captions(history);
+        self.step = 0;
+        self.elapsed = 0.0;
+        self.error = error;
+    }
+
+    fn last_step(&self) -> usize {
+        self.frames.len().saturating_sub(1)
+    }
+
+    /// Moves the replay on by `dt` seconds, stepping forward when it's time.
+    pub fn update(&mut self, dt: f32) {
+        if !self.playing {
+            return;
+        }
+        self.elapsed += dt * self.speed;
+        if self.elapsed >= STEP_SECS {
+            self.elapsed = 0.0;
+            self.forward();
+        }
+        if self.step == self.last_step() {
+            self.playing = false;
+        }
+    }
+
+    fn forward(&mut self) {
+        self.step = (self.step + 1).min(self.last_step());
+    }
+
+    fn back(&mut self) {
+        self.step = self.step.saturating_sub(1);
+    }
+
+    fn handle(&mut self, action: &ButtonAction) {
+        match action {
+            ButtonAction::StepBack => {
+                self.playing = false;
+                self.back();
+            }
+            ButtonAction::StepForward => {
+                self.playing = false;
+                self.forward();
+            }
+            ButtonAction::PlayPause => {
+                // Playing from the end starts the hand over
+                if !self.playing && self.step == self.last_step() {
+                    self.step = 0;
+                }
+                self.playing = !self.playing;
+                self.elapsed = 0.0;
+            }
+            ButtonAction::Slower => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+            ButtonAction::Faster => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+            ButtonAction::PrevHand if self.hand > 0 => self.load_hand(self.hand - 1),
+            ButtonAction::NextHand if self.hand + 1 < self.hands.len() => self.load_hand(self.hand + 1),
+            _ => {}
+        }
+    }
+
+    fn game(&self) -> Option<&Game> {
+        self.frames.get(self.step)
+    }
+}
+
+/// A hand history, or the one inside an archive record.
+fn hand_from(mut value: Value) -> Option<HandHistory> {
+    if let Some(history) = value.get_mut("history") {
+        value = history.take();
+    }
+    serde_json::from_value(value).ok()
+}
+
+/// What happened to get to each frame: the deal, then each betting action.
+fn captions(history: &HandHistory) -> Vec<String> {
+    let name = |player: usize| history.seats.iter()
+        .find(|seat| seat.seat == player)
+        .map_or("?".to_string(), |seat| seat.name.clone());
+    let blinds: Vec<String> = history.actions.iter()
+        .filter_map(|action| match action.kind {
+            HandActionKind::Posted(BlindKind::Ante) => Some(format!("{} antes {}", name(action.player), action.amount)),
+            HandActionKind::Posted(_) => Some(format!("{} posts {}", name(action.player), action.amount)),
+            HandActionKind::Acted(_) => None,
+        })
+        .collect();
+
+    let mut captions = vec![format!("Dealt. {}", blinds.join(", "))];
+    for action in &history.actions {
+        let HandActionKind::Acted(kind) = action.kind else {
+            continue;
+        };
+        let what = match kind {
+            PlayerAction::Fold => "folds".to_string(),
+            PlayerAction::Check => "checks".to_string(),
+            PlayerAction::Call => format!("calls ${}", action.amount),
+            PlayerAction::Bet => format!("bets ${}", action.total),
+            PlayerAction::Raise => format!("raises to ${}", action.total),
+            PlayerAction::AllIn => format!("is all-in for ${}", action.amount),
+        };
+        captions.push(format!("{} {}", name(action.player), what));
+    }
+    captions
+}
+
+/// Runs the replayer until the window is closed.
+///
+/// Left and right step through the hand, space plays and pauses, up and
+/// down change the speed, and page up and page down move between hands.
+pub async fn run(mut replay: Replay) {
+    let y = WINDOW_HEIGHT - 60.0;
+    let mut buttons = vec![
+        Button::new(50.0, y, 90.0, 40.0, "Prev hand", ButtonAction::PrevHand),
+        Button::new(150.0, y, 90.0, 40.0, "Back", ButtonAction::StepBack),
+        Button::new(250.0, y, 90.0, 40.0, "Play", ButtonAction::PlayPause),
+        Button::new(350.0, y, 90.0, 40.0, "Forward", ButtonAction::StepForward),
+        Button::new(450.0, y, 90.0, 40.0, "Slower", ButtonAction::Slower),
+        Button::new(550.0, y, 90.0, 40.0, "Faster", ButtonAction::Faster),
+        Button::new(650.0, y, 90.0, 40.0, "Next hand", ButtonAction::NextHand),
+    ];
+    let keys = [
+        (KeyCode::Left, ButtonAction::StepBack),
+        (KeyCode::Right, ButtonAction::StepForward),
+        (KeyCode::Space, ButtonAction::PlayPause),
+        (KeyCode::Down, ButtonAction::Slower),
+        (KeyCode::Up, ButtonAction::Faster),
+        (KeyCode::PageUp, ButtonAction::PrevHand),
+        (KeyCode::PageDown, ButtonAction::NextHand),
+    ];
+
+    loop {
+        // Process input
+        let mouse_position = Vec2::new(mouse_position().0, mouse_position().1);
+        for button in &buttons {
+            if button.is_hovered(mouse_position) && is_mouse_button_pressed(MouseButton::Left) {
+                replay.handle(&button.action);
+            }
+        }
+        for (key, action) in &keys {
+            if is_key_pressed(*key) {
+                replay.handle(action);
+            }
+        }
+        replay.update(get_frame_time());
+
+        // Render
+        match replay.game() {
+            Some(game) => draw_table(game),
+            None => clear_background(DARKGRAY),
+        }
+
+        let history = &replay.hands[replay.hand];
+        let status = format!(
+            "Hand #{} ({} of {}) - step {} of {} - {}x",
+            history.hand_number,
+            replay.hand + 1,
+            replay.hands.len(),
+            replay.step + 1,
+            replay.frames.len(),
+            replay.speed,
+        );
+        draw_text(status, 60.0, 20.0, 20.0, TEXT_COLOR);
+        if let Some(caption) = replay.captions.get(replay.step) {
+            draw_text(caption, 60.0, 42.0, 20.0, YELLOW);
+        }
+        if let Some(error) = &replay.error {
+            draw_text(error, 60.0, WINDOW_HEIGHT - 75.0, 20.0, RED);
+        }
+
+        buttons[2].text = if replay.playing { "Pause" } else { "Play" }.to_string();
+        for button in &buttons {
+            button.draw(button.is_hovered(mouse_position), true);
+        }
+
+        next_frame().await;
+    }
+}
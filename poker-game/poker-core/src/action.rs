@@ -0,0 +1,68 @@
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::game::Round;
+
+/// What happened as a result of a successful player action.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ActionOutcome {
+    /// Betting continues with the given player to act.
+    NextToAct(usize),
+    /// The betting round closed and the next street was dealt.
+    RoundComplete(Round),
+    /// The hand is over and the pots have been awarded.
+    HandComplete,
+}
+
+/// An action the player to act is allowed to take, with its chip bounds.
+///
+/// Bet and raise bounds are the total the player's commitment for the round
+/// can be raised to, as passed to `Game::player_bet`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum LegalAction {
+    Fold,
+    Check,
+    /// Call for `amount` more chips, which is all-in if it is the whole stack.
+    Call { amount: u32 },
+    Bet { min: u32, max: u32 },
+    Raise { min: u32, max: u32 },
+    /// Put in the remaining `amount` chips.
+    AllIn { amount: u32 },
+}
+
+/// Why a player action was rejected. The game is left unchanged.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ActionError {
+    HandNotInProgress,
+    UnknownPlayer,
+    NotYourTurn,
+    InsufficientChips,
+    CannotCheckFacingBet,
+    NothingToCall,
+    RaiseTooSmall { min: u32 },
+    RaiseTooLarge { max: u32 },
+    RaiseCapReached,
+    /// Only a full raise gives a player who has already acted another raise.
+    BettingNotReopened,
+    SeatTaken,
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::HandNotInProgress => write!(f, "No hand is in progress"),
+            ActionError::UnknownPlayer => write!(f, "No such player at this table"),
+            ActionError::NotYourTurn => write!(f, "It is not your turn to act"),
+            ActionError::InsufficientChips => write!(f, "Not enough chips"),
+            ActionError::CannotCheckFacingBet => write!(f, "Cannot check facing a bet"),
+            ActionError::NothingToCall => write!(f, "There is no bet to call"),
+            ActionError::RaiseTooSmall { min } => write!(f, "Raise must be to at least {}", min),
+            ActionError::RaiseTooLarge { max } => write!(f, "Raise can be to at most {}", max),
+            ActionError::RaiseCapReached => write!(f, "No more raises are allowed this round"),
+            ActionError::BettingNotReopened => write!(f, "An all-in short of a full raise does not re-open the betting"),
+            ActionError::SeatTaken => write!(f, "That seat is taken"),
+        }
+    }
+}
+
+impl std::error::Error for ActionError {}
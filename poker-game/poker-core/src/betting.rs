@@ -0,0 +1,42 @@
+use serde::{Serialize, Deserialize};
+
+use crate::game::Round;
+
+/// Limits on how much a player may bet or raise.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub enum BettingStructure {
+    /// Raises must be at least the size of the previous bet or raise, up to all-in.
+    #[default]
+    NoLimit,
+    /// As no-limit, but a raise can be at most the size of the pot after calling.
+    PotLimit,
+    /// Bets and raises are a fixed size, `small_bet` preflop and on the flop and
+    /// `big_bet` on the turn and river, with at most `raise_cap` bets and raises
+    /// per round. Preflop the big blind counts as the opening bet.
+    FixedLimit {
+        small_bet: u32,
+        big_bet: u32,
+        raise_cap: u32,
+    },
+}
+
+impl BettingStructure {
+    /// Fixed bet size for the given round, if the structure has one.
+    pub fn fixed_bet_size(&self, round: &Round) -> Option<u32> {
+        match self {
+            BettingStructure::FixedLimit { small_bet, big_bet, .. } => match round {
+                Round::PreFlop | Round::Flop => Some(*small_bet),
+                _ => Some(*big_bet),
+            },
+            _ => None,
+        }
+    }
+
+    /// Maximum number of bets and raises in a round, if capped.
+    pub fn raise_cap(&self) -> Option<u32> {
+        match self {
+            BettingStructure::FixedLimit { raise_cap, .. } => Some(*raise_cap),
+            _ => None,
+        }
+    }
+}
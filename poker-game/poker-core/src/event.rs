@@ -0,0 +1,66 @@
+//! What happened in a hand, in order, so clients can animate and log it.
+
+use serde::{Serialize, Deserialize};
+
+use crate::game::{Card, Round};
+use crate::hand::HandStrength;
+use crate::pot::PotAward;
+
+/// A forced bet posted at the start of a hand.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum BlindKind {
+    Ante,
+    SmallBlind,
+    BigBlind,
+}
+
+/// A betting decision a player made.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PlayerAction {
+    Fold,
+    Check,
+    Call,
+    Bet,
+    Raise,
+    AllIn,
+}
+
+/// A player's hole cards turned face up at showdown.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ShownHand {
+    pub player: usize,
+    pub cards: Vec<Card>,
+    pub strength: Option<HandStrength>,
+}
+
+/// Something that happened at the table, as recorded by `Game`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    PlayerJoined { player: usize, name: String, chips: u32 },
+    PlayerSatOut { player: usize },
+    PlayerSatIn { player: usize },
+    /// The player gave up their seat, cashing out `chips`.
+    PlayerLeft { player: usize, chips: u32 },
+    HandStarted { hand_number: u32, dealer_index: usize },
+    BlindPosted { player: usize, kind: BlindKind, amount: u32 },
+    /// Hole cards dealt to `player`, or `None` where they're hidden from the viewer.
+    CardsDealt { player: usize, cards: Option<Vec<Card>> },
+    /// `amount` is the chips the action put in, and `total` the player's
+    /// commitment for the round afterwards.
+    PlayerActed { player: usize, action: PlayerAction, amount: u32, total: u32 },
+    StreetDealt { round: Round, cards: Vec<Card> },
+    Showdown { hands: Vec<ShownHand> },
+    PotAwarded(PotAward),
+}
+
+impl GameEvent {
+    /// The event as `viewer` may see it, hiding other players' hole cards.
+    pub fn visible_to(&self, viewer: Option<usize>) -> GameEvent {
+        match self {
+            GameEvent::CardsDealt { player, .. } if viewer != Some(*player) => {
+                GameEvent::CardsDealt { player: *player, cards: None }
+            }
+            event => event.clone(),
+        }
+    }
+}
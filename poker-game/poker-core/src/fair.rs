@@ -0,0 +1,138 @@
+//! Commit-reveal shuffling so players can check the server didn't stack the deck.
+//!
+//! Before a hand the server publishes `commit(server_seed)`. Players add their
+//! own seeds, and the deck is shuffled with `combine_seeds` of the server seed
+//! and every client seed in the order received. Once the hand is over the
+//! server seed is revealed, and anyone can check it against the commitment and
+//! rebuild the exact deck with `shuffled_deck`.
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+use crate::game::Deck;
+
+pub type Seed = [u8; 32];
+
+/// Generates a new secret server seed from the operating system's RNG.
+pub fn generate_server_seed() -> Seed {
+    let mut seed = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut seed);
+    seed
+}
+
+/// Hex encoded SHA-256 of the server seed, published before the hand.
+pub fn commit(server_seed: &Seed) -> String {
+    hex::encode(Sha256::digest(server_seed))
+}
+
+/// Mixes the client seeds into the server seed to give the shuffle seed.
+///
+/// Each client seed is length-prefixed so seeds can't be shifted between
+/// neighbours to produce the same input.
+pub fn combine_seeds(server_seed: &Seed, client_seeds: &[String]) -> Seed {
+    let mut hasher = Sha256::new();
+    hasher.update(server_seed);
+    for client_seed in client_seeds {
+        hasher.update((client_seed.len() as u64).to_be_bytes());
+        hasher.update(client_seed.as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// A full deck shuffled deterministically from `seed`.
+pub fn shuffled_deck(seed: &Seed) -> Deck {
+    let mut rng = ChaCha20Rng::from_seed(*seed);
+    let mut deck = Deck::new();
+    deck.shuffle_with(&mut rng);
+    deck
+}
+
+/// Everything needed to verify a finished hand's shuffle.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SeedReveal {
+    pub hand_number: u32,
+    pub commitment: String,
+    /// Hex encoded server seed.
+    pub server_seed: String,
+    pub client_seeds: Vec<String>,
+}
+
+impl SeedReveal {
+    pub fn new(hand_number: u32, server_seed: &Seed, client_seeds: Vec<String>) -> Self {
+        SeedReveal {
+            hand_number,
+            commitment: commit(server_seed),
+            server_seed: hex::encode(server_seed),
+            client_seeds,
+        }
+    }
+
+    fn decoded_server_seed(&self) -> Option<Seed> {
+        hex::decode(&self.server_seed).ok()?.try_into().ok()
+    }
+
+    /// Whether the revealed server seed matches the commitment published
+    /// before the hand.
+    pub fn matches_commitment(&self, commitment: &str) -> bool {
+        self.decoded_server_seed()
+            .is_some_and(|seed| commit(&seed) == commitment)
+    }
+
+    /// Rebuilds the deck the hand was dealt from, before any cards were drawn.
+    pub fn deck(&self) -> Option<Deck> {
+        let server_seed = self.decoded_server_seed()?;
+        Some(shuffled_deck(&combine_seeds(&server_seed, &self.client_seeds)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Card;
+    use crate::testing::seated_game;
+
+    fn dealt_hands(deck: Deck) -> Vec<Vec<Card>> {
+        let mut game = seated_game(&[100, 100, 100]);
+        game.deal_cards_from(deck);
+        game.players.into_iter().map(|p| p.hand).collect()
+    }
+
+    #[test]
+    fn reveal_matches_its_commitment_and_rebuilds_the_deck() {
+        let server_seed = generate_server_seed();
+        let client_seeds = vec!["a1".to_string(), "b2".to_string()];
+        let commitment = commit(&server_seed);
+        let dealt = shuffled_deck(&combine_seeds(&server_seed, &client_seeds));
+
+        let reveal = SeedReveal::new(7, &server_seed, client_seeds);
+        assert!(reveal.matches_commitment(&commitment));
+        let rebuilt = reveal.deck().unwrap();
+        assert_eq!(rebuilt.cards, dealt.cards);
+        assert_eq!(dealt_hands(rebuilt), dealt_hands(dealt));
+    }
+
+    #[test]
+    fn tampered_seeds_are_caught() {
+        let server_seed = generate_server_seed();
+        let commitment = commit(&server_seed);
+        let honest = SeedReveal::new(7, &server_seed, vec!["a1".to_string()]);
+
+        let mut other_seed = server_seed;
+        other_seed[0] ^= 1;
+        let swapped = SeedReveal::new(7, &other_seed, vec!["a1".to_string()]);
+        assert!(!swapped.matches_commitment(&commitment));
+
+        // A changed client seed still matches, but deals a different deck
+        let mut changed = honest.clone();
+        changed.client_seeds = vec!["a2".to_string()];
+        assert!(changed.matches_commitment(&commitment));
+        assert_ne!(changed.deck().unwrap().cards, honest.deck().unwrap().cards);
+
+        let mut garbled = honest;
+        garbled.server_seed.push('0');
+        assert!(!garbled.matches_commitment(&commitment));
+        assert!(garbled.deck().is_none());
+    }
+}
@@ -0,0 +1,1298 @@
+use serde::{Serialize, Deserialize};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::action::{ActionError, ActionOutcome, LegalAction};
+use crate::betting::BettingStructure;
+use crate::event::{BlindKind, GameEvent, PlayerAction, ShownHand};
+use crate::hand::{self, HandStrength};
+use crate::history::{HandHistory, SeatRecord};
+use crate::pot::{self, Pot, PotAward};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum GameState {
+    Setup,
+    Dealing,
+    Betting,
+    PlayerTurn,
+    ShowDown,
+    GameOver,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Game {
+    pub players: Vec<Player>,
+    pub deck: Deck,
+    pub community_cards: Vec<Card>,
+    pub current_bet: u32,
+    pub pot: u32,
+    pub current_player_index: usize,
+    pub state: GameState,
+    pub round: Round,
+    pub pot_awards: Vec<PotAward>,
+    pub blinds: Blinds,
+    pub betting_structure: BettingStructure,
+    /// Size of the last full bet or raise this round, the minimum for the next raise.
+    pub last_raise_size: u32,
+    /// Bets and raises made this round, including the big blind preflop.
+    pub raises_this_round: u32,
+    pub dealer_index: usize,
+    pub small_blind_index: usize,
+    pub big_blind_index: usize,
+    /// Number of hands dealt so far.
+    pub hand_number: u32,
+    /// Source of randomness for shuffling. Seed it to make hands reproducible.
+    #[serde(skip, default = "ChaCha20Rng::from_entropy")]
+    pub rng: ChaCha20Rng,
+    /// Events recorded since they were last drained.
+    #[serde(skip)]
+    pub events: Vec<GameEvent>,
+    /// History of the hand in progress.
+    #[serde(skip)]
+    pub hand_history: Option<HandHistory>,
+    /// History of the last hand to finish, until someone takes it.
+    #[serde(skip)]
+    pub last_hand: Option<HandHistory>,
+}
+
+/// Forced bets posted at the start of every hand.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Blinds {
+    pub small_blind: u32,
+    pub big_blind: u32,
+    pub ante: u32,
+}
+
+impl Default for Blinds {
+    fn default() -> Self {
+        Blinds {
+            small_blind: 5,
+            big_blind: 10,
+            ante: 0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Round {
+    PreFlop,
+    Flop,
+    Turn,
+    River,
+    Complete,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        // Initialize a new game
+        let mut game = Game {
+            players: vec![],
+            deck: Deck::new(),
+            community_cards: vec![],
+            current_bet: 0,
+            pot: 0,
+            current_player_index: 0,
+            state: GameState::Setup,
+            round: Round::PreFlop,
+            pot_awards: vec![],
+            blinds: Blinds::default(),
+            betting_structure: BettingStructure::default(),
+            last_raise_size: 0,
+            raises_this_round: 0,
+            dealer_index: 0,
+            small_blind_index: 0,
+            big_blind_index: 0,
+            hand_number: 0,
+            rng: ChaCha20Rng::from_entropy(),
+            events: vec![],
+            hand_history: None,
+            last_hand: None,
+        };
+        
+        // Shuffle the deck
+        game.deck.shuffle_with(&mut game.rng);
+        
+        game
+    }
+    
+    /// Creates a game whose shuffles are fully determined by `seed`, so a hand
+    /// can be replayed exactly.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut game = Game::new();
+        game.rng = ChaCha20Rng::seed_from_u64(seed);
+        game.deck = Deck::new();
+        game.deck.shuffle_with(&mut game.rng);
+        game
+    }
+    
+    pub fn with_blinds(blinds: Blinds) -> Self {
+        let mut game = Game::new();
+        game.blinds = blinds;
+        game
+    }
+    
+    /// Seats a player in the first empty seat, returning the seat number.
+    pub fn add_player(&mut self, name: String, chips: u32) -> usize {
+        let seat = self.players.iter()
+            .position(|p| p.status == SeatStatus::Empty)
+            .unwrap_or(self.players.len());
+        self.seat_player(seat, name, chips);
+        seat
+    }
+    
+    /// Seats a player at seat number `seat`, adding empty seats before it as
+    /// needed.
+    pub fn take_seat(&mut self, seat: usize, name: String, chips: u32) -> Result<(), ActionError> {
+        if self.players.get(seat).is_some_and(|p| p.status != SeatStatus::Empty) {
+            return Err(ActionError::SeatTaken);
+        }
+        self.seat_player(seat, name, chips);
+        Ok(())
+    }
+    
+    fn seat_player(&mut self, seat: usize, name: String, chips: u32) {
+        while self.players.len() <= seat {
+            self.players.push(Player::empty_seat());
+        }
+        self.emit(GameEvent::PlayerJoined { player: seat, name: name.clone(), chips });
+        self.players[seat] = Player {
+            name,
+            chips,
+            hand: vec![],
+            // Players joining mid-hand wait for the next deal
+            has_folded: self.state == GameState::PlayerTurn,
+            has_acted: false,
+            contributed: 0,
+            street_contribution: 0,
+            is_all_in: false,
+            status: SeatStatus::Playing,
+            facing_incomplete_raise: false,
+        };
+    }
+    
+    /// Checks `player` is sitting at the table and not on their way out.
+    fn check_seated(&self, player: usize) -> Result<(), ActionError> {
+        match self.players.get(player).map(|p| p.status) {
+            Some(SeatStatus::Playing | SeatStatus::SittingOut) => Ok(()),
+            _ => Err(ActionError::UnknownPlayer),
+        }
+    }
+    
+    /// Stops dealing `player` in from the next hand, keeping their seat and chips.
+    pub fn sit_out(&mut self, player: usize) -> Result<(), ActionError> {
+        self.check_seated(player)?;
+        if self.players[player].status != SeatStatus::SittingOut {
+            self.players[player].status = SeatStatus::SittingOut;
+            self.emit(GameEvent::PlayerSatOut { player });
+        }
+        Ok(())
+    }
+    
+    /// Deals a player who was sitting out back in from the next hand.
+    pub fn sit_in(&mut self, player: usize) -> Result<(), ActionError> {
+        self.check_seated(player)?;
+        if self.players[player].status != SeatStatus::Playing {
+            self.players[player].status = SeatStatus::Playing;
+            self.emit(GameEvent::PlayerSatIn { player });
+        }
+        Ok(())
+    }
+    
+    /// Gives up `player`'s seat. A player still in the hand folds, and is
+    /// cashed out once the hand is over.
+    pub fn leave_table(&mut self, player: usize) -> Result<(), ActionError> {
+        self.check_seated(player)?;
+        let hand_in_progress = self.state == GameState::PlayerTurn;
+        if !hand_in_progress || (self.players[player].has_folded && self.players[player].contributed == 0) {
+            self.cash_out(player);
+            return Ok(());
+        }
+        
+        self.players[player].status = SeatStatus::Leaving;
+        if self.players[player].has_folded {
+            // Already folded, but what they put in stays in the pot
+            return Ok(());
+        }
+        if player == self.current_player_index {
+            self.player_fold(player)?;
+            return Ok(());
+        }
+        
+        // Folding out of turn can still leave a single player or close the round
+        self.players[player].has_folded = true;
+        self.record_action(player, PlayerAction::Fold, 0);
+        if !self.award_if_one_left() && self.betting_round_complete() {
+            self.next_round();
+        }
+        Ok(())
+    }
+    
+    /// Calls off the hand in progress, giving everyone back what they put
+    /// in so the chips are as they were when it was dealt. Anyone who left
+    /// during the hand is cashed out.
+    pub fn void_hand(&mut self) {
+        if self.state != GameState::PlayerTurn {
+            return;
+        }
+        for player in &mut self.players {
+            player.chips += player.contributed;
+            player.contributed = 0;
+            player.street_contribution = 0;
+            player.is_all_in = false;
+            player.has_acted = false;
+            player.facing_incomplete_raise = false;
+            player.hand.clear();
+        }
+        self.community_cards.clear();
+        self.deck = Deck::new();
+        self.current_bet = 0;
+        self.pot = 0;
+        self.pot_awards.clear();
+        self.state = GameState::GameOver;
+        self.round = Round::Complete;
+        self.hand_history = None;
+        
+        for player in 0..self.players.len() {
+            if self.players[player].status == SeatStatus::Leaving {
+                self.cash_out(player);
+            }
+        }
+    }
+    
+    /// Empties the seat, handing back the player's chips.
+    fn cash_out(&mut self, player: usize) {
+        let chips = self.players[player].chips;
+        self.players[player] = Player::empty_seat();
+        self.emit(GameEvent::PlayerLeft { player, chips });
+    }
+    
+    /// Records an event, adding it to the history of the hand in progress.
+    fn emit(&mut self, event: GameEvent) {
+        if let Some(history) = &mut self.hand_history {
+            history.record(&event);
+        }
+        self.events.push(event);
+    }
+    
+    /// Takes the events recorded since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<GameEvent> {
+        std::mem::take(&mut self.events)
+    }
+    
+    /// Whether enough players are sat in with chips to start a hand.
+    pub fn can_deal(&self) -> bool {
+        self.players.iter()
+            .filter(|p| p.status == SeatStatus::Playing && p.chips > 0)
+            .count() >= 2
+    }
+    
+    /// Starts a new hand from a freshly shuffled deck.
+    pub fn deal_cards(&mut self) {
+        let mut deck = Deck::new();
+        deck.shuffle_with(&mut self.rng);
+        self.deal_cards_from(deck);
+    }
+    
+    /// Starts a new hand dealing from `deck`: moves the button, deals hole
+    /// cards and posts the antes and blinds. Does nothing unless two players
+    /// have chips.
+    ///
+    /// Hole cards go one at a time to each player starting left of the
+    /// button, twice round, followed by the flop, turn and river, so a deck
+    /// built with `Deck::from_order` can stack a specific scenario.
+    pub fn deal_cards_from(&mut self, deck: Deck) {
+        if !self.can_deal() {
+            return;
+        }
+        
+        // Clear any existing hands and community cards
+        self.community_cards.clear();
+        for player in &mut self.players {
+            player.hand.clear();
+            player.has_acted = false;
+            player.facing_incomplete_raise = false;
+            // Players without chips or sitting out, and empty seats, miss the hand
+            player.has_folded = player.chips == 0 || player.status != SeatStatus::Playing;
+            player.contributed = 0;
+            player.street_contribution = 0;
+            player.is_all_in = false;
+        }
+        
+        // Move the button on to the next player in the hand
+        if self.hand_number > 0 || self.players[self.dealer_index].has_folded {
+            self.dealer_index = self.next_seat_in_hand(self.dealer_index);
+        }
+        self.hand_number += 1;
+        let seats = self.players.iter()
+            .enumerate()
+            .filter(|(_, p)| !p.has_folded)
+            .map(|(seat, p)| SeatRecord { seat, name: p.name.clone(), stack: p.chips, hole_cards: vec![] })
+            .collect();
+        self.hand_history = Some(HandHistory::new(
+            self.hand_number,
+            self.blinds.clone(),
+            self.betting_structure.clone(),
+            self.dealer_index,
+            seats,
+        ));
+        self.emit(GameEvent::HandStarted {
+            hand_number: self.hand_number,
+            dealer_index: self.dealer_index,
+        });
+        
+        self.deck = deck;
+        
+        // Deal two cards to each player, starting left of the button
+        let seat_count = self.players.len();
+        for _ in 0..2 {
+            for offset in 1..=seat_count {
+                let player = &mut self.players[(self.dealer_index + offset) % seat_count];
+                if player.has_folded {
+                    continue;
+                }
+                if let Some(card) = self.deck.draw_card() {
+                    player.hand.push(card);
+                }
+            }
+        }
+        for offset in 1..=seat_count {
+            let player = (self.dealer_index + offset) % seat_count;
+            if !self.players[player].has_folded {
+                let cards = Some(self.players[player].hand.clone());
+                self.emit(GameEvent::CardsDealt { player, cards });
+            }
+        }
+        
+        // Reset game state
+        self.current_bet = 0;
+        self.pot = 0;
+        self.pot_awards.clear();
+        self.state = GameState::PlayerTurn;
+        self.round = Round::PreFlop;
+        
+        self.post_forced_bets();
+    }
+    
+    fn post_forced_bets(&mut self) {
+        // Antes are dead money and don't count towards calling the blinds
+        if self.blinds.ante > 0 {
+            for i in 0..self.players.len() {
+                if !self.players[i].has_folded {
+                    let posted = self.commit_chips(i, self.blinds.ante);
+                    self.players[i].street_contribution -= posted;
+                    self.emit(GameEvent::BlindPosted { player: i, kind: BlindKind::Ante, amount: posted });
+                }
+            }
+        }
+        
+        // Heads-up the button posts the small blind
+        let in_hand = self.players.iter().filter(|p| !p.has_folded).count();
+        self.small_blind_index = if in_hand == 2 {
+            self.dealer_index
+        } else {
+            self.next_seat_in_hand(self.dealer_index)
+        };
+        self.big_blind_index = self.next_seat_in_hand(self.small_blind_index);
+        let small_blind = self.commit_chips(self.small_blind_index, self.blinds.small_blind);
+        self.emit(GameEvent::BlindPosted {
+            player: self.small_blind_index,
+            kind: BlindKind::SmallBlind,
+            amount: small_blind,
+        });
+        let big_blind = self.commit_chips(self.big_blind_index, self.blinds.big_blind);
+        self.emit(GameEvent::BlindPosted {
+            player: self.big_blind_index,
+            kind: BlindKind::BigBlind,
+            amount: big_blind,
+        });
+        self.current_bet = self.blinds.big_blind;
+        self.last_raise_size = self.blinds.big_blind;
+        self.raises_this_round = 1;
+        
+        // Preflop action starts left of the big blind
+        self.current_player_index = self.big_blind_index;
+        self.advance_to_next_actor();
+        
+        // Blinds and antes may have put everyone all-in already
+        if self.betting_round_complete() {
+            self.next_round();
+        }
+    }
+    
+    /// Index of the next player after `from` who was dealt into the hand.
+    fn next_seat_in_hand(&self, from: usize) -> usize {
+        let seat_count = self.players.len();
+        (1..=seat_count)
+            .map(|offset| (from + offset) % seat_count)
+            .find(|&i| !self.players[i].has_folded)
+            .unwrap_or(from)
+    }
+    
+    pub fn next_player(&mut self) {
+        self.advance_to_next_actor();
+        
+        if self.betting_round_complete() {
+            self.next_round();
+        }
+    }
+    
+    /// The round is over once everyone who can still bet has acted and
+    /// matched the highest commitment.
+    fn betting_round_complete(&self) -> bool {
+        self.players.iter()
+            .filter(|p| p.can_act())
+            .all(|p| p.has_acted && p.street_contribution == self.current_bet)
+    }
+    
+    fn advance_to_next_actor(&mut self) {
+        // Find the next player who hasn't folded or gone all-in
+        let start_index = self.current_player_index;
+        loop {
+            self.current_player_index = (self.current_player_index + 1) % self.players.len();
+            
+            // If we've gone all the way around, break
+            if self.current_player_index == start_index {
+                break;
+            }
+            
+            // If this player can still act, break
+            if self.players[self.current_player_index].can_act() {
+                break;
+            }
+        }
+    }
+    
+    pub fn next_round(&mut self) {
+        // Reset player actions and round commitments
+        for player in &mut self.players {
+            player.has_acted = false;
+            player.facing_incomplete_raise = false;
+            player.street_contribution = 0;
+        }
+        
+        // Move to the next round
+        let dealt_before = self.community_cards.len();
+        match self.round {
+            Round::PreFlop => {
+                // Deal the flop (3 community cards)
+                for _ in 0..3 {
+                    if let Some(card) = self.deck.draw_card() {
+                        self.community_cards.push(card);
+                    }
+                }
+                self.round = Round::Flop;
+            },
+            Round::Flop => {
+                // Deal the turn (1 more community card)
+                if let Some(card) = self.deck.draw_card() {
+                    self.community_cards.push(card);
+                }
+                self.round = Round::Turn;
+            },
+            Round::Turn => {
+                // Deal the river (1 more community card)
+                if let Some(card) = self.deck.draw_card() {
+                    self.community_cards.push(card);
+                }
+                self.round = Round::River;
+            },
+            Round::River => {
+                // Show down
+                self.state = GameState::ShowDown;
+                self.round = Round::Complete;
+                let hands = self.players.iter()
+                    .enumerate()
+                    .filter(|(_, p)| !p.has_folded)
+                    .map(|(i, p)| ShownHand {
+                        player: i,
+                        cards: p.hand.clone(),
+                        strength: self.hand_strength(i),
+                    })
+                    .collect();
+                self.emit(GameEvent::Showdown { hands });
+                self.award_pots();
+            },
+            Round::Complete => {
+                // Game over
+                self.state = GameState::GameOver;
+            },
+        }
+        
+        if self.community_cards.len() > dealt_before {
+            self.emit(GameEvent::StreetDealt {
+                round: self.round.clone(),
+                cards: self.community_cards[dealt_before..].to_vec(),
+            });
+        }
+        
+        // Reset current bet for the new round
+        self.current_bet = 0;
+        self.last_raise_size = 0;
+        self.raises_this_round = 0;
+        
+        if self.state == GameState::PlayerTurn {
+            // With at most one player able to bet, run out the remaining streets
+            if self.players.iter().filter(|p| p.can_act()).count() <= 1 {
+                self.next_round();
+            } else {
+                // Postflop action starts left of the button
+                self.current_player_index = self.dealer_index;
+                self.advance_to_next_actor();
+            }
+        }
+    }
+    
+    /// Moves chips from a player's stack into the pot, going all-in if the
+    /// player cannot cover the full amount.
+    fn commit_chips(&mut self, player_index: usize, amount: u32) -> u32 {
+        let player = &mut self.players[player_index];
+        let amount = amount.min(player.chips);
+        player.chips -= amount;
+        player.contributed += amount;
+        player.street_contribution += amount;
+        if player.chips == 0 {
+            player.is_all_in = true;
+        }
+        self.pot += amount;
+        amount
+    }
+    
+    /// Gives everyone except `raiser` another chance to act after a raise.
+    /// Only a full raise lets those who already acted raise again.
+    fn reopen_action(&mut self, raiser: usize, full_raise: bool) {
+        for (i, player) in self.players.iter_mut().enumerate() {
+            if i == raiser {
+                continue;
+            }
+            if full_raise {
+                player.facing_incomplete_raise = false;
+            } else if player.has_acted {
+                player.facing_incomplete_raise = true;
+            }
+            player.has_acted = false;
+        }
+    }
+    
+    /// Chips the player to act needs to put in to match the current bet.
+    pub fn amount_to_call(&self) -> u32 {
+        let player = &self.players[self.current_player_index];
+        self.current_bet.saturating_sub(player.street_contribution)
+    }
+    
+    /// Smallest total a full bet or raise can be made to this round.
+    pub fn min_raise_to(&self) -> u32 {
+        if let Some(bet_size) = self.betting_structure.fixed_bet_size(&self.round) {
+            return self.current_bet + bet_size;
+        }
+        self.current_bet + self.last_raise_size.max(self.blinds.big_blind)
+    }
+    
+    /// Largest total the betting structure allows a bet or raise to, ignoring
+    /// the player's stack. `None` once the round's raise cap is reached.
+    pub fn max_raise_to(&self) -> Option<u32> {
+        if self.betting_structure.raise_cap().is_some_and(|cap| self.raises_this_round >= cap) {
+            return None;
+        }
+        match self.betting_structure {
+            BettingStructure::NoLimit => Some(u32::MAX),
+            // A pot sized raise is the pot after calling
+            BettingStructure::PotLimit => Some(self.current_bet + self.pot + self.amount_to_call()),
+            BettingStructure::FixedLimit { .. } => Some(self.min_raise_to()),
+        }
+    }
+    
+    /// Legal range for the total the player to act can bet or raise to, or
+    /// `None` if they cannot make a full raise. A player too short for the
+    /// minimum may still be able to go all-in.
+    pub fn raise_bounds(&self) -> Option<(u32, u32)> {
+        let player = &self.players[self.current_player_index];
+        if player.facing_incomplete_raise {
+            return None;
+        }
+        let all_in_total = player.street_contribution + player.chips;
+        let min = self.min_raise_to();
+        let max = self.max_raise_to()?.min(all_in_total);
+        if max < min {
+            return None;
+        }
+        Some((min, max))
+    }
+    
+    /// Whether the player to act may put their whole stack in under the
+    /// betting structure.
+    pub fn can_go_all_in(&self) -> bool {
+        let player = &self.players[self.current_player_index];
+        let all_in_total = player.street_contribution + player.chips;
+        if all_in_total <= self.current_bet {
+            return true;
+        }
+        if player.facing_incomplete_raise {
+            return false;
+        }
+        self.max_raise_to().is_some_and(|max| all_in_total <= max)
+    }
+    
+    /// Actions `player` may take right now. Empty unless it is their turn in a
+    /// hand that is in progress.
+    pub fn legal_actions(&self, player: usize) -> Vec<LegalAction> {
+        if self.check_turn(player).is_err() {
+            return vec![];
+        }
+        
+        let chips = self.players[player].chips;
+        let to_call = self.amount_to_call();
+        let mut actions = vec![LegalAction::Fold];
+        if to_call == 0 {
+            actions.push(LegalAction::Check);
+        } else {
+            actions.push(LegalAction::Call { amount: to_call.min(chips) });
+        }
+        if let Some((min, max)) = self.raise_bounds() {
+            if self.current_bet == 0 {
+                actions.push(LegalAction::Bet { min, max });
+            } else {
+                actions.push(LegalAction::Raise { min, max });
+            }
+        }
+        if self.can_go_all_in() {
+            actions.push(LegalAction::AllIn { amount: chips });
+        }
+        actions
+    }
+    
+    /// Records a bet or raise to `total`, fully re-opening the action only if
+    /// it is at least a full raise.
+    fn record_raise(&mut self, raiser: usize, total: u32) {
+        let raise_size = total - self.current_bet;
+        let full_raise = total >= self.min_raise_to();
+        self.current_bet = total;
+        if full_raise {
+            self.last_raise_size = raise_size;
+            self.raises_this_round += 1;
+        }
+        self.reopen_action(raiser, full_raise);
+    }
+    
+    fn record_action(&mut self, player: usize, action: PlayerAction, amount: u32) {
+        // Having responded, the player is no longer facing the short all-in
+        self.players[player].facing_incomplete_raise = false;
+        let total = self.players[player].street_contribution;
+        self.emit(GameEvent::PlayerActed { player, action, amount, total });
+    }
+    
+    /// Checks that `player` is the one to act in a hand that is in progress.
+    fn check_turn(&self, player: usize) -> Result<(), ActionError> {
+        if self.state != GameState::PlayerTurn {
+            return Err(ActionError::HandNotInProgress);
+        }
+        if player >= self.players.len() {
+            return Err(ActionError::UnknownPlayer);
+        }
+        if player != self.current_player_index {
+            return Err(ActionError::NotYourTurn);
+        }
+        Ok(())
+    }
+    
+    /// Describes where the hand stands after an action has been applied.
+    fn action_outcome(&self, round_before: Round) -> ActionOutcome {
+        if self.state != GameState::PlayerTurn {
+            ActionOutcome::HandComplete
+        } else if self.round != round_before {
+            ActionOutcome::RoundComplete(self.round.clone())
+        } else {
+            ActionOutcome::NextToAct(self.current_player_index)
+        }
+    }
+    
+    /// Bets or raises to `amount`, the player's total commitment for the round.
+    pub fn player_bet(&mut self, player: usize, amount: u32) -> Result<ActionOutcome, ActionError> {
+        self.check_turn(player)?;
+        
+        if self.players[player].facing_incomplete_raise {
+            return Err(ActionError::BettingNotReopened);
+        }
+        
+        // Must be within the legal raise range for the betting structure
+        let max = self.max_raise_to().ok_or(ActionError::RaiseCapReached)?;
+        let min = self.min_raise_to();
+        if amount < min {
+            return Err(ActionError::RaiseTooSmall { min });
+        }
+        if amount > max {
+            return Err(ActionError::RaiseTooLarge { max });
+        }
+        let additional = amount - self.players[player].street_contribution;
+        if additional > self.players[player].chips {
+            return Err(ActionError::InsufficientChips);
+        }
+        
+        let round = self.round.clone();
+        let action = if self.current_bet == 0 { PlayerAction::Bet } else { PlayerAction::Raise };
+        self.players[player].has_acted = true;
+        self.commit_chips(player, additional);
+        self.record_raise(player, amount);
+        self.record_action(player, action, additional);
+        
+        self.next_player();
+        Ok(self.action_outcome(round))
+    }
+    
+    pub fn player_call(&mut self, player: usize) -> Result<ActionOutcome, ActionError> {
+        self.check_turn(player)?;
+        let to_call = self.amount_to_call();
+        if to_call == 0 {
+            return Err(ActionError::NothingToCall);
+        }
+        
+        // A player who can't cover the bet calls all-in for what they have
+        let round = self.round.clone();
+        self.players[player].has_acted = true;
+        let called = self.commit_chips(player, to_call);
+        self.record_action(player, PlayerAction::Call, called);
+        
+        self.next_player();
+        Ok(self.action_outcome(round))
+    }
+    
+    pub fn player_all_in(&mut self, player: usize) -> Result<ActionOutcome, ActionError> {
+        self.check_turn(player)?;
+        if !self.can_go_all_in() {
+            if self.players[player].facing_incomplete_raise {
+                return Err(ActionError::BettingNotReopened);
+            }
+            return Err(match self.max_raise_to() {
+                Some(max) => ActionError::RaiseTooLarge { max },
+                None => ActionError::RaiseCapReached,
+            });
+        }
+        
+        let round = self.round.clone();
+        let chips = self.players[player].chips;
+        self.players[player].has_acted = true;
+        self.commit_chips(player, chips);
+        
+        // An all-in short of a full raise still raises the bet to match, but
+        // doesn't change the minimum raise
+        let total = self.players[player].street_contribution;
+        if total > self.current_bet {
+            self.record_raise(player, total);
+        }
+        self.record_action(player, PlayerAction::AllIn, chips);
+        
+        self.next_player();
+        Ok(self.action_outcome(round))
+    }
+    
+    pub fn player_check(&mut self, player: usize) -> Result<ActionOutcome, ActionError> {
+        self.check_turn(player)?;
+        if self.amount_to_call() > 0 {
+            return Err(ActionError::CannotCheckFacingBet);
+        }
+        
+        let round = self.round.clone();
+        self.players[player].has_acted = true;
+        self.record_action(player, PlayerAction::Check, 0);
+        
+        self.next_player();
+        Ok(self.action_outcome(round))
+    }
+    
+    pub fn player_fold(&mut self, player: usize) -> Result<ActionOutcome, ActionError> {
+        self.check_turn(player)?;
+        
+        let round = self.round.clone();
+        let folding_player = &mut self.players[player];
+        folding_player.has_folded = true;
+        folding_player.has_acted = true;
+        self.record_action(player, PlayerAction::Fold, 0);
+        
+        if !self.award_if_one_left() {
+            self.next_player();
+        }
+        Ok(self.action_outcome(round))
+    }
+    
+    /// Ends the hand if everyone but one player has folded, giving them the
+    /// pot. Returns whether it did.
+    fn award_if_one_left(&mut self) -> bool {
+        let active_players = self.players.iter().filter(|p| !p.has_folded).count();
+        if active_players != 1 {
+            return false;
+        }
+        self.state = GameState::GameOver;
+        self.award_pots();
+        true
+    }
+    
+    pub fn get_winner(&self) -> Option<usize> {
+        self.get_winners().first().copied()
+    }
+
+    /// Returns every player holding the best hand, so ties can split the pot.
+    pub fn get_winners(&self) -> Vec<usize> {
+        let active_players: Vec<usize> = self.players.iter()
+            .enumerate()
+            .filter(|(_, p)| !p.has_folded)
+            .map(|(i, _)| i)
+            .collect();
+        self.best_hands(&active_players)
+    }
+
+    fn best_hands(&self, candidates: &[usize]) -> Vec<usize> {
+        // A lone candidate wins without needing a complete board
+        if candidates.len() <= 1 {
+            return candidates.to_vec();
+        }
+
+        let mut best: Option<HandStrength> = None;
+        let mut winners = vec![];
+        for &index in candidates {
+            let Some(strength) = self.hand_strength(index) else {
+                continue;
+            };
+            match best.as_ref().map(|b| strength.cmp(b)) {
+                Some(Ordering::Less) => {}
+                Some(Ordering::Equal) => winners.push(index),
+                _ => {
+                    best = Some(strength);
+                    winners = vec![index];
+                }
+            }
+        }
+        winners
+    }
+
+    /// Evaluates a player's best hand using their hole cards and the board.
+    pub fn hand_strength(&self, player_index: usize) -> Option<HandStrength> {
+        let player = self.players.get(player_index)?;
+        let cards: Vec<Card> = player.hand.iter()
+            .chain(self.community_cards.iter())
+            .copied()
+            .collect();
+        hand::evaluate(&cards)
+    }
+
+    /// Main pot followed by any side pots for the chips committed this hand.
+    pub fn pots(&self) -> Vec<Pot> {
+        pot::build_pots(&self.players)
+    }
+
+    /// Pays out every pot to the best eligible hand, splitting ties.
+    ///
+    /// Odd chips from a split go to the winners closest to the left of the
+    /// button.
+    pub fn award_pots(&mut self) -> Vec<PotAward> {
+        let mut awards = vec![];
+        for (pot_index, pot) in self.pots().into_iter().enumerate() {
+            let mut winners = self.best_hands(&pot.eligible_players);
+            if winners.is_empty() {
+                // Only players who have since folded put chips in, so they go
+                // to whoever is left
+                winners = self.get_winners();
+            }
+            if winners.is_empty() {
+                continue;
+            }
+
+            let share = pot.amount / winners.len() as u32;
+            let remainder = pot.amount % winners.len() as u32;
+            let seat_count = self.players.len();
+            winners.sort_by_key(|&w| (w + seat_count - self.dealer_index % seat_count - 1) % seat_count);
+            for (i, &winner) in winners.iter().enumerate() {
+                let odd_chip = if (i as u32) < remainder { 1 } else { 0 };
+                self.players[winner].chips += share + odd_chip;
+            }
+            let award = PotAward { pot_index, winners, amount: pot.amount };
+            self.emit(GameEvent::PotAwarded(award.clone()));
+            awards.push(award);
+        }
+
+        self.pot = 0;
+        self.pot_awards = awards.clone();
+        self.last_hand = self.hand_history.take();
+        
+        // Anyone who left during the hand takes their chips now
+        for player in 0..self.players.len() {
+            if self.players[player].status == SeatStatus::Leaving {
+                self.cash_out(player);
+            }
+        }
+        awards
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Player {
+    pub name: String,
+    pub chips: u32,
+    pub hand: Vec<Card>,
+    pub has_folded: bool,
+    pub has_acted: bool,
+    /// Chips put into the pot so far this hand.
+    pub contributed: u32,
+    /// Chips put into the pot during the current betting round.
+    pub street_contribution: u32,
+    pub is_all_in: bool,
+    #[serde(default)]
+    pub status: SeatStatus,
+    /// Acted on the last full bet and has since only faced an all-in short of
+    /// a full raise, so may call or fold but not raise.
+    #[serde(default)]
+    pub facing_incomplete_raise: bool,
+}
+
+/// Whether a seat is taken, and whether its player is being dealt in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeatStatus {
+    #[default]
+    Playing,
+    /// Keeps the seat and chips, but isn't dealt in or charged blinds.
+    SittingOut,
+    /// Folded out of the hand in progress and cashed out when it ends.
+    Leaving,
+    Empty,
+}
+
+impl Player {
+    fn empty_seat() -> Self {
+        Player {
+            name: String::new(),
+            chips: 0,
+            hand: vec![],
+            has_folded: true,
+            has_acted: false,
+            contributed: 0,
+            street_contribution: 0,
+            is_all_in: false,
+            status: SeatStatus::Empty,
+            facing_incomplete_raise: false,
+        }
+    }
+    
+    /// Whether the player can still make betting decisions this hand.
+    pub fn can_act(&self) -> bool {
+        !self.has_folded && !self.is_all_in
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Deck {
+    pub cards: Vec<Card>,
+}
+
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deck {
+    pub fn new() -> Self {
+        // Initialize a new deck of cards
+        let mut cards = vec![];
+        for suit in ['♥', '♦', '♣', '♠'] {
+            for rank in 2..=10 {
+                cards.push(Card { suit, rank: Rank::Number(rank as u8) });
+            }
+            for rank in ['J', 'Q', 'K', 'A'] {
+                let card_rank = match rank {
+                    'J' => Rank::Jack,
+                    'Q' => Rank::Queen,
+                    'K' => Rank::King,
+                    'A' => Rank::Ace,
+                    _ => unreachable!(),
+                };
+                cards.push(Card { suit, rank: card_rank });
+            }
+        }
+        Deck { cards }
+    }
+
+    /// Builds a stacked deck that deals `cards` in the order given.
+    pub fn from_order(cards: Vec<Card>) -> Self {
+        let mut cards = cards;
+        // Cards are drawn from the end
+        cards.reverse();
+        Deck { cards }
+    }
+
+    pub fn shuffle(&mut self) {
+        // Implement deck shuffling
+        let mut rng = rand::thread_rng();
+        self.shuffle_with(&mut rng);
+    }
+
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.cards.as_mut_slice().shuffle(rng);
+    }
+
+    pub fn draw_card(&mut self) -> Option<Card> {
+        // Implement card drawing
+        self.cards.pop()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    pub suit: char,
+    pub rank: Rank,
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rank {
+    Number(u8),
+    Jack,
+    Queen,
+    King,
+    Ace,
+}
+
+impl Rank {
+    /// Numeric value of the rank, with aces high (2-14).
+    pub fn value(&self) -> u8 {
+        match self {
+            Rank::Number(n) => *n,
+            Rank::Jack => 11,
+            Rank::Queen => 12,
+            Rank::King => 13,
+            Rank::Ace => 14,
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rank::Number(n) => write!(f, "{}", n),
+            Rank::Jack => write!(f, "J"),
+            Rank::Queen => write!(f, "Q"),
+            Rank::King => write!(f, "K"),
+            Rank::Ace => write!(f, "A"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{check_down, seated_game, stacked_game, stacks, total_chips};
+
+    #[test]
+    fn flush_beats_straight() {
+        // Heads up the button is dealt second: Player 2, Player 1, Player 2, Player 1
+        let mut game = stacked_game(&[100, 100], "9c 2h 10d 7h Jh Qs 8h Kh 3c");
+        check_down(&mut game);
+
+        assert_eq!(game.pot_awards.len(), 1);
+        assert_eq!(game.pot_awards[0].winners, vec![0]);
+        assert_eq!(stacks(&game), vec![110, 90]);
+    }
+    
+    #[test]
+    fn short_all_in_does_not_reopen_the_betting() {
+        let deck = "2c 3d 4h 7c 8d 9h As Ks Qs Js 10s";
+        let mut game = stacked_game(&[1000, 1000, 150], deck);
+        game.player_bet(0, 100).unwrap();
+        game.player_call(1).unwrap();
+        // 50 more is short of the 90 a full raise needs
+        game.player_all_in(2).unwrap();
+        
+        assert_eq!(game.legal_actions(0), vec![LegalAction::Fold, LegalAction::Call { amount: 50 }]);
+        assert_eq!(game.player_bet(0, 240), Err(ActionError::BettingNotReopened));
+        assert_eq!(game.player_all_in(0), Err(ActionError::BettingNotReopened));
+        game.player_call(0).unwrap();
+        assert_eq!(game.legal_actions(1), vec![LegalAction::Fold, LegalAction::Call { amount: 50 }]);
+        game.player_call(1).unwrap();
+        
+        assert_eq!(game.round, Round::Flop);
+        assert_eq!(game.pot, 450);
+    }
+    
+    #[test]
+    fn calling_puts_in_only_the_difference() {
+        let deck = "2c 3d 4h 7c 8d 9h As Ks Qs Js 10s";
+        let mut game = stacked_game(&[100, 100, 100], deck);
+        game.player_bet(0, 30).unwrap();
+        game.drain_events();
+        // The blinds already have 5 and 10 in
+        game.player_call(1).unwrap();
+        game.player_call(2).unwrap();
+        
+        let calls: Vec<(u32, u32)> = game.drain_events().into_iter()
+            .filter_map(|event| match event {
+                GameEvent::PlayerActed { action: PlayerAction::Call, amount, total, .. } => Some((amount, total)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(calls, vec![(25, 30), (20, 30)]);
+        assert_eq!(stacks(&game), vec![70, 70, 70]);
+        assert_eq!(game.pot, 90);
+    }
+    
+    #[test]
+    fn heads_up_the_button_posts_the_small_blind_and_acts_first() {
+        let mut game = seated_game(&[100, 100]);
+        game.deal_cards();
+        assert_eq!((game.dealer_index, game.small_blind_index, game.big_blind_index), (0, 0, 1));
+        assert_eq!(stacks(&game), vec![95, 90]);
+        assert_eq!(game.current_player_index, 0);
+        
+        // After the flop the big blind acts first
+        game.player_call(0).unwrap();
+        game.player_check(1).unwrap();
+        assert_eq!(game.round, Round::Flop);
+        assert_eq!(game.current_player_index, 1);
+    }
+    
+    #[test]
+    fn button_skips_players_sitting_out() {
+        let mut game = seated_game(&[100, 100, 100, 100]);
+        game.sit_out(1).unwrap();
+        game.deal_cards();
+        assert_eq!((game.dealer_index, game.small_blind_index, game.big_blind_index), (0, 2, 3));
+        assert!(game.players[1].has_folded && game.players[1].hand.is_empty());
+        
+        game.void_hand();
+        game.deal_cards();
+        assert_eq!((game.dealer_index, game.small_blind_index, game.big_blind_index), (2, 3, 0));
+        assert!(game.players[1].hand.is_empty());
+    }
+    
+    #[test]
+    fn antes_are_dead_money() {
+        let mut game = seated_game(&[100, 100, 100]);
+        game.blinds.ante = 1;
+        game.deal_cards();
+        assert_eq!(stacks(&game), vec![99, 94, 89]);
+        assert_eq!(game.pot, 18);
+        // The ante doesn't count towards calling the big blind
+        assert_eq!(game.amount_to_call(), 10);
+        
+        game.player_call(0).unwrap();
+        game.player_call(1).unwrap();
+        game.player_check(2).unwrap();
+        assert_eq!(game.round, Round::Flop);
+        assert_eq!(stacks(&game), vec![89, 89, 89]);
+        assert_eq!(game.pot, 33);
+    }
+    
+    #[test]
+    fn pot_limit_raises_are_capped_at_the_pot() {
+        let mut game = seated_game(&[1000, 1000, 1000]);
+        game.betting_structure = BettingStructure::PotLimit;
+        game.deal_cards();
+        // Calling 10 makes the pot 25, so the most is a raise of 25 more
+        assert_eq!(game.raise_bounds(), Some((20, 35)));
+        game.player_bet(0, 35).unwrap();
+        
+        // The small blind calls 30 into 50, then may raise 80 more
+        assert_eq!(game.player_bet(1, 116), Err(ActionError::RaiseTooLarge { max: 115 }));
+        game.player_bet(1, 115).unwrap();
+        assert!(!game.can_go_all_in());
+    }
+    
+    #[test]
+    fn fixed_limit_stops_raising_at_the_cap() {
+        let mut game = seated_game(&[1000, 1000, 1000]);
+        game.betting_structure = BettingStructure::FixedLimit { small_bet: 10, big_bet: 20, raise_cap: 3 };
+        game.deal_cards();
+        // Preflop the big blind is the first of the three
+        assert_eq!(game.raise_bounds(), Some((20, 20)));
+        game.player_bet(0, 20).unwrap();
+        game.player_bet(1, 30).unwrap();
+        
+        assert_eq!(game.legal_actions(2), vec![LegalAction::Fold, LegalAction::Call { amount: 20 }]);
+        assert_eq!(game.player_bet(2, 40), Err(ActionError::RaiseCapReached));
+        assert_eq!(game.player_all_in(2), Err(ActionError::RaiseCapReached));
+        
+        
+        // The count starts again each round, and the turn bets the big bet
+        game.player_call(2).unwrap();
+        game.player_call(0).unwrap();
+        assert_eq!(game.round, Round::Flop);
+        for _ in 0..3 {
+            game.player_check(game.current_player_index).unwrap();
+        }
+        assert_eq!(game.round, Round::Turn);
+        assert_eq!(game.raise_bounds(), Some((20, 20)));
+    }
+    
+    /// Chips handed back to players who left, by seat.
+    fn cashed_out(game: &mut Game) -> Vec<(usize, u32)> {
+        game.drain_events().into_iter()
+            .filter_map(|event| match event {
+                GameEvent::PlayerLeft { player, chips } => Some((player, chips)),
+                _ => None,
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn leaving_after_folding_waits_for_the_hand_to_end() {
+        let mut game = stacked_game(&[100, 100, 100], "2c 3d 4h 7c 8d 9h As Ks Qs Js 10s");
+        game.player_call(0).unwrap();
+        // The small blind's 5 chips stay in the pot
+        game.player_fold(1).unwrap();
+        game.leave_table(1).unwrap();
+        assert_eq!(game.players[1].status, SeatStatus::Leaving);
+        assert_eq!(total_chips(&game), 300);
+        assert!(cashed_out(&mut game).is_empty());
+        
+        check_down(&mut game);
+        assert_eq!(cashed_out(&mut game), vec![(1, 95)]);
+        assert_eq!(game.players[1].status, SeatStatus::Empty);
+        assert_eq!(total_chips(&game) + 95, 300);
+    }
+    
+    #[test]
+    fn leaving_mid_hand_folds_out_of_turn() {
+        let mut game = stacked_game(&[100, 100, 100], "2c 3d 4h 7c 8d 9h As Ks Qs Js 10s");
+        game.player_bet(0, 30).unwrap();
+        // The big blind goes while the small blind is still to act
+        game.leave_table(2).unwrap();
+        assert!(game.players[2].has_folded);
+        assert_eq!(game.current_player_index, 1);
+        
+        game.player_fold(1).unwrap();
+        assert_eq!(game.state, GameState::GameOver);
+        assert_eq!(cashed_out(&mut game), vec![(2, 90)]);
+        assert_eq!(stacks(&game), vec![115, 95, 0]);
+        assert_eq!(total_chips(&game) + 90, 300);
+    }
+    
+    #[test]
+    fn voiding_a_hand_gives_back_the_stacks_it_started_with() {
+        let mut game = stacked_game(&[100, 100, 100], "2c 3d 4h 7c 8d 9h As Ks Qs Js 10s");
+        game.player_bet(0, 30).unwrap();
+        game.leave_table(2).unwrap();
+        game.player_call(1).unwrap();
+        // Bet into on the flop, so the hand is still being played
+        game.player_bet(1, 20).unwrap();
+        assert_eq!(game.state, GameState::PlayerTurn);
+        cashed_out(&mut game);
+        
+        // The big blind who left is owed their whole starting stack
+        game.void_hand();
+        assert_eq!(cashed_out(&mut game), vec![(2, 100)]);
+        assert_eq!(game.players[2].status, SeatStatus::Empty);
+        assert_eq!(stacks(&game), vec![100, 100, 0]);
+        assert_eq!(game.pot, 0);
+        assert!(game.players.iter().all(|p| p.contributed == 0 && p.hand.is_empty()));
+    }
+}
@@ -0,0 +1,180 @@
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::game::Card;
+
+/// Poker hand categories, ordered from weakest to strongest.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandCategory {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+impl fmt::Display for HandCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            HandCategory::HighCard => "High Card",
+            HandCategory::OnePair => "One Pair",
+            HandCategory::TwoPair => "Two Pair",
+            HandCategory::ThreeOfAKind => "Three of a Kind",
+            HandCategory::Straight => "Straight",
+            HandCategory::Flush => "Flush",
+            HandCategory::FullHouse => "Full House",
+            HandCategory::FourOfAKind => "Four of a Kind",
+            HandCategory::StraightFlush => "Straight Flush",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Comparable strength of a five card hand.
+///
+/// Strengths compare by category first and then by `kickers`, which hold the
+/// rank values (2-14) that decide ties within a category, most significant
+/// first. For straights only the top card is kept, so the A-2-3-4-5 wheel
+/// ranks as a five-high straight.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandStrength {
+    pub category: HandCategory,
+    pub kickers: Vec<u8>,
+}
+
+impl fmt::Display for HandStrength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.category)
+    }
+}
+
+/// Evaluates the best five card hand that can be made from `cards`.
+///
+/// Returns `None` when fewer than five cards are given.
+pub fn evaluate(cards: &[Card]) -> Option<HandStrength> {
+    if cards.len() < 5 {
+        return None;
+    }
+
+    let mut best: Option<HandStrength> = None;
+    let n = cards.len();
+    for a in 0..n {
+        for b in a + 1..n {
+            for c in b + 1..n {
+                for d in c + 1..n {
+                    for e in d + 1..n {
+                        let strength = evaluate_five(&[cards[a], cards[b], cards[c], cards[d], cards[e]]);
+                        if best.as_ref().is_none_or(|current| strength > *current) {
+                            best = Some(strength);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+fn evaluate_five(cards: &[Card; 5]) -> HandStrength {
+    let mut values: Vec<u8> = cards.iter().map(|card| card.rank.value()).collect();
+    values.sort_unstable_by(|a, b| b.cmp(a));
+
+    let is_flush = cards.iter().all(|card| card.suit == cards[0].suit);
+    let straight_high = straight_high_card(&values);
+
+    // Group equal ranks, largest groups first and higher ranks breaking ties
+    let mut groups: Vec<(usize, u8)> = vec![];
+    for &value in &values {
+        match groups.iter_mut().find(|(_, v)| *v == value) {
+            Some(group) => group.0 += 1,
+            None => groups.push((1, value)),
+        }
+    }
+    groups.sort_unstable_by(|a, b| b.cmp(a));
+    let grouped: Vec<u8> = groups.iter().map(|&(_, v)| v).collect();
+
+    let (category, kickers) = match (straight_high, is_flush, groups[0].0, groups.get(1).map(|g| g.0)) {
+        (Some(high), true, _, _) => (HandCategory::StraightFlush, vec![high]),
+        (_, _, 4, _) => (HandCategory::FourOfAKind, grouped),
+        (_, _, 3, Some(2)) => (HandCategory::FullHouse, grouped),
+        (_, true, _, _) => (HandCategory::Flush, values),
+        (Some(high), _, _, _) => (HandCategory::Straight, vec![high]),
+        (_, _, 3, _) => (HandCategory::ThreeOfAKind, grouped),
+        (_, _, 2, Some(2)) => (HandCategory::TwoPair, grouped),
+        (_, _, 2, _) => (HandCategory::OnePair, grouped),
+        _ => (HandCategory::HighCard, values),
+    };
+
+    HandStrength { category, kickers }
+}
+
+/// Returns the top card of a straight, given five rank values sorted high to low.
+fn straight_high_card(values: &[u8]) -> Option<u8> {
+    let distinct = values.windows(2).all(|pair| pair[0] != pair[1]);
+    if !distinct {
+        return None;
+    }
+    if values[0] - values[4] == 4 {
+        return Some(values[0]);
+    }
+    // A-2-3-4-5: the ace plays low
+    if values == [14, 5, 4, 3, 2] {
+        return Some(5);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::cards;
+
+    fn category(text: &str) -> HandCategory {
+        evaluate(&cards(text)).unwrap().category
+    }
+
+    #[test]
+    fn ranks_every_category() {
+        assert_eq!(category("Ah Kd 9c 7s 4h 3d 2c"), HandCategory::HighCard);
+        assert_eq!(category("Ah Ad 9c 7s 4h 3d 2c"), HandCategory::OnePair);
+        assert_eq!(category("Ah Ad 9c 9s 4h 3d 2c"), HandCategory::TwoPair);
+        assert_eq!(category("Ah Ad Ac 9s 4h 3d 2c"), HandCategory::ThreeOfAKind);
+        assert_eq!(category("9h 8d 7c 6s 5h 3d 2c"), HandCategory::Straight);
+        assert_eq!(category("Ah Jh 9h 7h 4h 3d 2c"), HandCategory::Flush);
+        assert_eq!(category("Ah Ad Ac 9s 9h 3d 2c"), HandCategory::FullHouse);
+        assert_eq!(category("Ah Ad Ac As 9h 3d 2c"), HandCategory::FourOfAKind);
+        assert_eq!(category("9h 8h 7h 6h 5h 3d 2c"), HandCategory::StraightFlush);
+    }
+
+    #[test]
+    fn kickers_break_ties_within_a_category() {
+        let aces_king = evaluate(&cards("Ah Ad Kc 7s 4h 3d 2c")).unwrap();
+        let aces_queen = evaluate(&cards("Ah Ad Qc 7s 4h 3d 2c")).unwrap();
+        assert!(aces_king > aces_queen);
+
+        // Only the best five cards count, so the sixth and seventh don't play
+        let board = "Ah Ad Kc Qs Jh";
+        let low = evaluate(&cards(&format!("{} 3d 2c", board))).unwrap();
+        let high = evaluate(&cards(&format!("{} 4d 2h", board))).unwrap();
+        assert_eq!(low, high);
+    }
+
+    #[test]
+    fn needs_five_cards() {
+        assert_eq!(evaluate(&cards("Ah Ad Ac As")), None);
+    }
+
+    #[test]
+    fn wheel_is_the_lowest_straight() {
+        let wheel = evaluate(&cards("Ah 2d 3c 4s 5h 9d Kc")).unwrap();
+        assert_eq!(wheel.category, HandCategory::Straight);
+        assert_eq!(wheel.kickers, vec![5]);
+
+        let six_high = evaluate(&cards("6h 2d 3c 4s 5h 9d Kc")).unwrap();
+        assert!(six_high > wheel);
+    }
+}
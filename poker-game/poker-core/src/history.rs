@@ -0,0 +1,573 @@
+//! Records of finished hands, and export to the PokerStars text format that
+//! hand tracking tools import.
+
+use serde::{Serialize, Deserialize};
+use std::fmt::{self, Write};
+
+use crate::action::ActionError;
+use crate::betting::BettingStructure;
+use crate::event::{BlindKind, GameEvent, PlayerAction, ShownHand};
+use crate::game::{Blinds, Card, Deck, Game, Rank, Round};
+use crate::pot::PotAward;
+
+/// A player dealt into the hand.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SeatRecord {
+    pub seat: usize,
+    pub name: String,
+    /// Chips in front of the player before any blinds were posted.
+    pub stack: u32,
+    pub hole_cards: Vec<Card>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum HandActionKind {
+    Posted(BlindKind),
+    Acted(PlayerAction),
+}
+
+/// A blind or betting action, in the order they happened.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HandAction {
+    pub round: Round,
+    pub player: usize,
+    pub kind: HandActionKind,
+    /// Chips the action put in.
+    pub amount: u32,
+    /// The player's commitment for the round afterwards.
+    pub total: u32,
+}
+
+/// A recorded action the engine wouldn't accept when replaying a hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayError {
+    /// Index of the action in `HandHistory::actions`.
+    pub action: usize,
+    pub error: ActionError,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Action {} can't be replayed: {}", self.action + 1, self.error)
+    }
+}
+
+/// Everything that happened in one hand, from the deal to the pots being
+/// awarded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HandHistory {
+    pub hand_number: u32,
+    /// When the hand was dealt, in seconds since the Unix epoch.
+    pub started_at: u64,
+    pub blinds: Blinds,
+    pub betting_structure: BettingStructure,
+    pub button: usize,
+    pub seats: Vec<SeatRecord>,
+    pub actions: Vec<HandAction>,
+    pub board: Vec<Card>,
+    pub showdown: Vec<ShownHand>,
+    /// Pots in order, with winners listed in the order odd chips went to them.
+    pub awards: Vec<PotAward>,
+}
+
+impl HandHistory {
+    pub fn new(hand_number: u32, blinds: Blinds, betting_structure: BettingStructure, button: usize, seats: Vec<SeatRecord>) -> Self {
+        let started_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        HandHistory {
+            hand_number,
+            started_at,
+            blinds,
+            betting_structure,
+            button,
+            seats,
+            actions: vec![],
+            board: vec![],
+            showdown: vec![],
+            awards: vec![],
+        }
+    }
+
+    /// The round the next action will be in, going by the board dealt so far.
+    fn current_round(&self) -> Round {
+        match self.board.len() {
+            0 => Round::PreFlop,
+            3 => Round::Flop,
+            4 => Round::Turn,
+            _ => Round::River,
+        }
+    }
+
+    /// Adds a game event to the record. Events from outside the hand are ignored.
+    pub fn record(&mut self, event: &GameEvent) {
+        let round = self.current_round();
+        match event {
+            GameEvent::BlindPosted { player, kind, amount } => {
+                let total = if *kind == BlindKind::Ante { 0 } else { *amount };
+                self.actions.push(HandAction {
+                    round,
+                    player: *player,
+                    kind: HandActionKind::Posted(*kind),
+                    amount: *amount,
+                    total,
+                });
+            }
+            GameEvent::CardsDealt { player, cards } => {
+                if let (Some(seat), Some(cards)) = (self.seat_mut(*player), cards) {
+                    seat.hole_cards = cards.clone();
+                }
+            }
+            GameEvent::PlayerActed { player, action, amount, total } => {
+                self.actions.push(HandAction {
+                    round,
+                    player: *player,
+                    kind: HandActionKind::Acted(*action),
+                    amount: *amount,
+                    total: *total,
+                });
+            }
+            GameEvent::StreetDealt { cards, .. } => self.board.extend(cards.iter().copied()),
+            GameEvent::Showdown { hands } => self.showdown = hands.clone(),
+            GameEvent::PotAwarded(award) => self.awards.push(award.clone()),
+            _ => {}
+        }
+    }
+
+    /// The history as `viewer` may see it: their own hole cards and those
+    /// shown down, but not what anyone else folded or mucked.
+    pub fn visible_to(&self, viewer: Option<usize>) -> HandHistory {
+        let mut history = self.clone();
+        for seat in &mut history.seats {
+            let shown = self.showdown.iter().any(|s| s.player == seat.seat);
+            if !shown && viewer != Some(seat.seat) {
+                seat.hole_cards.clear();
+            }
+        }
+        history
+    }
+
+    /// The UTC day the hand was dealt on, as `YYYY-MM-DD`.
+    pub fn started_on(&self) -> String {
+        let (year, month, day) = civil_date(self.started_at / 86_400);
+        format!("{}-{:02}-{:02}", year, month, day)
+    }
+
+    fn seat(&self, seat: usize) -> Option<&SeatRecord> {
+        self.seats.iter().find(|s| s.seat == seat)
+    }
+
+    fn seat_mut(&mut self, seat: usize) -> Option<&mut SeatRecord> {
+        self.seats.iter_mut().find(|s| s.seat == seat)
+    }
+
+    fn name(&self, seat: usize) -> &str {
+        self.seat(seat).map_or("?", |s| s.name.as_str())
+    }
+
+    /// The bet nobody matched: the player who put in the most, and the chips
+    /// beyond what anyone else put in. The engine pays it out with the last
+    /// pot, but it's handed back rather than won.
+    pub fn uncalled_bet(&self) -> Option<(usize, u32)> {
+        let mut contributed: Vec<(usize, u32)> = self.seats.iter().map(|s| (s.seat, 0)).collect();
+        for action in &self.actions {
+            if let Some((_, total)) = contributed.iter_mut().find(|(p, _)| *p == action.player) {
+                *total += action.amount;
+            }
+        }
+        contributed.sort_by_key(|&(_, total)| std::cmp::Reverse(total));
+        let (player, most) = *contributed.first()?;
+        let next = contributed.get(1).map_or(0, |&(_, total)| total);
+        let returned = self.awards.last()
+            .is_some_and(|award| award.winners == [player] && award.amount >= most - next);
+        (most > next && returned).then_some((player, most - next))
+    }
+
+    /// The pots as won, without the uncalled bet, leaving out any pot that
+    /// was nothing but the uncalled bet.
+    fn won_awards(&self) -> Vec<PotAward> {
+        let mut awards = self.awards.clone();
+        if let (Some((_, uncalled)), Some(last)) = (self.uncalled_bet(), awards.last_mut()) {
+            last.amount -= uncalled;
+            if last.amount == 0 {
+                awards.pop();
+            }
+        }
+        awards
+    }
+
+    /// Chips won from other players, not counting an uncalled bet returned.
+    pub fn total_pot(&self) -> u32 {
+        self.won_awards().iter().map(|a| a.amount).sum()
+    }
+
+    /// Chips each player won, in seat order.
+    pub fn winnings(&self) -> Vec<(usize, u32)> {
+        let mut winnings: Vec<(usize, u32)> = vec![];
+        for award in &self.won_awards() {
+            for (player, amount) in split_award(award) {
+                match winnings.iter_mut().find(|(p, _)| *p == player) {
+                    Some((_, won)) => *won += amount,
+                    None => winnings.push((player, amount)),
+                }
+            }
+        }
+        winnings.sort_unstable();
+        winnings
+    }
+
+    /// Exports the hand in PokerStars' text format, seen by `hero` whose
+    /// hole cards are shown as dealt. Seats are numbered from 1.
+    pub fn to_pokerstars(&self, table_name: &str, max_seats: usize, hero: Option<usize>) -> String {
+        let mut out = String::new();
+        let game = match self.betting_structure {
+            BettingStructure::NoLimit => "No Limit",
+            BettingStructure::PotLimit => "Pot Limit",
+            BettingStructure::FixedLimit { .. } => "Limit",
+        };
+        let _ = writeln!(out, "PokerStars Hand #{}: Hold'em {} ({}/{}) - {} UTC",
+            self.hand_number, game, self.blinds.small_blind, self.blinds.big_blind, format_utc(self.started_at));
+        let _ = writeln!(out, "Table '{}' {}-max Seat #{} is the button", table_name, max_seats, self.button + 1);
+        for seat in &self.seats {
+            let _ = writeln!(out, "Seat {}: {} ({} in chips)", seat.seat + 1, seat.name, seat.stack);
+        }
+
+        // Track stacks and the bet to match so all-ins and raise sizes read right
+        let mut stacks: Vec<(usize, u32)> = self.seats.iter().map(|s| (s.seat, s.stack)).collect();
+        let mut to_match = 0;
+        let mut round = Round::PreFlop;
+        let mut folded_on: Vec<(usize, Round)> = vec![];
+        let posts = self.actions.iter().filter(|a| matches!(a.kind, HandActionKind::Posted(_)));
+        for action in posts {
+            let name = self.name(action.player);
+            if let Some((_, stack)) = stacks.iter_mut().find(|(p, _)| *p == action.player) {
+                *stack = stack.saturating_sub(action.amount);
+            }
+            let _ = match action.kind {
+                HandActionKind::Posted(BlindKind::Ante) => writeln!(out, "{}: posts the ante {}", name, action.amount),
+                HandActionKind::Posted(BlindKind::SmallBlind) => writeln!(out, "{}: posts small blind {}", name, action.amount),
+                _ => writeln!(out, "{}: posts big blind {}", name, action.amount),
+            };
+            to_match = to_match.max(action.total);
+        }
+
+        let _ = writeln!(out, "*** HOLE CARDS ***");
+        if let Some(hero) = hero.and_then(|h| self.seat(h)) {
+            let _ = writeln!(out, "Dealt to {} {}", hero.name, cards(&hero.hole_cards));
+        }
+        // The uncalled bet goes back after the street it was made on
+        let uncalled = self.uncalled_bet();
+        let uncalled_on = uncalled.and_then(|(player, _)| self.actions.iter()
+            .rev()
+            .find(|a| a.player == player && a.amount > 0)
+            .map(|a| a.round.clone()));
+        let plays = self.actions.iter().filter(|a| matches!(a.kind, HandActionKind::Acted(_)));
+        let mut plays = plays.peekable();
+        for street in [Round::PreFlop, Round::Flop, Round::Turn, Round::River] {
+            if street != Round::PreFlop {
+                let dealt = match street {
+                    Round::Flop => 3,
+                    Round::Turn => 4,
+                    _ => 5,
+                };
+                if self.board.len() < dealt {
+                    break;
+                }
+                let header = match street {
+                    Round::Flop => format!("*** FLOP *** {}", cards(&self.board[..3])),
+                    Round::Turn => format!("*** TURN *** {} {}", cards(&self.board[..3]), cards(&self.board[3..4])),
+                    _ => format!("*** RIVER *** {} {}", cards(&self.board[..4]), cards(&self.board[4..5])),
+                };
+                let _ = writeln!(out, "{}", header);
+                to_match = 0;
+                round = street.clone();
+            }
+
+            while let Some(action) = plays.next_if(|a| a.round == street) {
+                let name = self.name(action.player);
+                let stack = stacks.iter_mut().find(|(p, _)| *p == action.player).map(|(_, s)| s);
+                let all_in = match stack {
+                    Some(stack) => {
+                        *stack = stack.saturating_sub(action.amount);
+                        *stack == 0 && action.amount > 0
+                    }
+                    None => false,
+                };
+                let suffix = if all_in { " and is all-in" } else { "" };
+                let HandActionKind::Acted(kind) = action.kind else {
+                    continue;
+                };
+                let line = match kind {
+                    PlayerAction::Fold => {
+                        folded_on.push((action.player, round.clone()));
+                        "folds".to_string()
+                    }
+                    PlayerAction::Check => "checks".to_string(),
+                    PlayerAction::Call => format!("calls {}", action.amount),
+                    _ if action.total <= to_match => format!("calls {}", action.amount),
+                    _ if to_match == 0 => format!("bets {}", action.amount),
+                    _ => format!("raises {} to {}", action.total - to_match, action.total),
+                };
+                to_match = to_match.max(action.total);
+                let _ = writeln!(out, "{}: {}{}", name, line, suffix);
+            }
+            if let (Some((player, amount)), Some(round)) = (uncalled, &uncalled_on) {
+                if *round == street {
+                    let _ = writeln!(out, "Uncalled bet ({}) returned to {}", amount, self.name(player));
+                }
+            }
+        }
+
+        let winnings = self.winnings();
+        let won = |player: usize| winnings.iter().find(|(p, _)| *p == player).map(|(_, w)| *w);
+        if !self.showdown.is_empty() {
+            let _ = writeln!(out, "*** SHOW DOWN ***");
+            for shown in &self.showdown {
+                let strength = shown.strength.as_ref().map(|s| format!(" ({})", s.category)).unwrap_or_default();
+                let _ = writeln!(out, "{}: shows {}{}", self.name(shown.player), cards(&shown.cards), strength);
+            }
+        }
+        let awards = self.won_awards();
+        for award in &awards {
+            let pot = match (awards.len(), award.pot_index) {
+                (1, _) => "pot".to_string(),
+                (_, 0) => "main pot".to_string(),
+                (_, n) => format!("side pot-{}", n),
+            };
+            for (player, amount) in split_award(award) {
+                let _ = writeln!(out, "{} collected {} from {}", self.name(player), amount, pot);
+            }
+        }
+
+        let _ = writeln!(out, "*** SUMMARY ***");
+        let _ = writeln!(out, "Total pot {} | Rake 0", self.total_pot());
+        if !self.board.is_empty() {
+            let _ = writeln!(out, "Board {}", cards(&self.board));
+        }
+        let posted = |kind: BlindKind| self.actions.iter()
+            .find(|a| a.kind == HandActionKind::Posted(kind))
+            .map(|a| a.player);
+        for seat in &self.seats {
+            let mut position = String::new();
+            if seat.seat == self.button {
+                position.push_str(" (button)");
+            }
+            if posted(BlindKind::SmallBlind) == Some(seat.seat) {
+                position.push_str(" (small blind)");
+            } else if posted(BlindKind::BigBlind) == Some(seat.seat) {
+                position.push_str(" (big blind)");
+            }
+            let shown = self.showdown.iter().find(|s| s.player == seat.seat);
+            let outcome = match (folded_on.iter().find(|(p, _)| *p == seat.seat), shown, won(seat.seat)) {
+                (Some((_, Round::PreFlop)), _, _) => "folded before Flop".to_string(),
+                (Some((_, round)), _, _) => format!("folded on the {}", street_name(round)),
+                (None, Some(shown), Some(amount)) => format!("showed {} and won ({})", cards(&shown.cards), amount),
+                (None, Some(shown), None) => format!("showed {} and lost", cards(&shown.cards)),
+                (None, None, Some(amount)) => format!("collected ({})", amount),
+                (None, None, None) => "didn't win".to_string(),
+            };
+            let _ = writeln!(out, "Seat {}: {}{} {}", seat.seat + 1, seat.name, position, outcome);
+        }
+        out
+    }
+
+    /// Plays the hand again on a fresh `Game`, returning the table after the
+    /// deal and after each betting action.
+    ///
+    /// Hole cards the history doesn't know, like a folded player's, are
+    /// dealt from the rest of the deck so the engine can run, but taken out
+    /// of the returned games.
+    pub fn replay(&self) -> Result<Vec<Game>, ReplayError> {
+        let mut game = Game::with_blinds(self.blinds.clone());
+        game.betting_structure = self.betting_structure.clone();
+        for seat in &self.seats {
+            let _ = game.take_seat(seat.seat, seat.name.clone(), seat.stack);
+        }
+        game.dealer_index = self.button;
+        game.deal_cards_from(self.stacked_deck(game.players.len()));
+        game.hand_number = self.hand_number;
+
+        let hidden: Vec<usize> = self.seats.iter()
+            .filter(|seat| seat.hole_cards.is_empty())
+            .map(|seat| seat.seat)
+            .collect();
+        let frame = |game: &Game| {
+            let mut frame = game.clone();
+            frame.events.clear();
+            for &seat in &hidden {
+                frame.players[seat].hand.clear();
+            }
+            frame
+        };
+
+        let mut frames = vec![frame(&game)];
+        for (i, action) in self.actions.iter().enumerate() {
+            let HandActionKind::Acted(kind) = action.kind else {
+                continue;
+            };
+            let player = action.player;
+            let result = match kind {
+                // Folding out of turn only happens by leaving the table
+                PlayerAction::Fold if player != game.current_player_index => game.leave_table(player),
+                PlayerAction::Fold => game.player_fold(player).map(|_| ()),
+                PlayerAction::Check => game.player_check(player).map(|_| ()),
+                PlayerAction::Call => game.player_call(player).map(|_| ()),
+                PlayerAction::Bet | PlayerAction::Raise => game.player_bet(player, action.total).map(|_| ()),
+                PlayerAction::AllIn => game.player_all_in(player).map(|_| ()),
+            };
+            result.map_err(|error| ReplayError { action: i, error })?;
+            frames.push(frame(&game));
+        }
+        Ok(frames)
+    }
+
+    /// A deck that deals the recorded hole cards and board to `seat_count`
+    /// seats, filling in whatever wasn't recorded.
+    fn stacked_deck(&self, seat_count: usize) -> Deck {
+        let known: Vec<Card> = self.seats.iter()
+            .flat_map(|seat| seat.hole_cards.iter())
+            .chain(self.board.iter())
+            .copied()
+            .collect();
+        let mut filler = Deck::new().cards.into_iter().filter(|card| !known.contains(card));
+
+        let mut order = vec![];
+        for round in 0..2 {
+            for offset in 1..=seat_count {
+                if let Some(seat) = self.seat((self.button + offset) % seat_count) {
+                    order.extend(seat.hole_cards.get(round).copied().or_else(|| filler.next()));
+                }
+            }
+        }
+        order.extend(self.board.iter().copied());
+        order.extend(filler.take(5 - self.board.len().min(5)));
+        Deck::from_order(order)
+    }
+}
+
+/// Splits a pot between its winners, the first listed taking any odd chips.
+fn split_award(award: &PotAward) -> Vec<(usize, u32)> {
+    if award.winners.is_empty() {
+        return vec![];
+    }
+    let share = award.amount / award.winners.len() as u32;
+    let remainder = award.amount as usize % award.winners.len();
+    award.winners.iter()
+        .enumerate()
+        .map(|(i, &w)| (w, share + u32::from(i < remainder)))
+        .collect()
+}
+
+fn street_name(round: &Round) -> &'static str {
+    match round {
+        Round::PreFlop => "Preflop",
+        Round::Flop => "Flop",
+        Round::Turn => "Turn",
+        _ => "River",
+    }
+}
+
+/// A card as PokerStars writes it, like `Td` or `As`.
+fn card(card: &Card) -> String {
+    let rank = match card.rank {
+        Rank::Number(10) => "T".to_string(),
+        rank => rank.to_string(),
+    };
+    let suit = match card.suit {
+        '♥' => 'h',
+        '♦' => 'd',
+        '♣' => 'c',
+        '♠' => 's',
+        other => other,
+    };
+    format!("{}{}", rank, suit)
+}
+
+fn cards(cards: &[Card]) -> String {
+    let cards: Vec<String> = cards.iter().map(card).collect();
+    format!("[{}]", cards.join(" "))
+}
+
+/// Formats Unix time as `YYYY/MM/DD HH:MM:SS`.
+fn format_utc(secs: u64) -> String {
+    let (year, month, day) = civil_date(secs / 86_400);
+    let time = secs % 86_400;
+    format!("{}/{:02}/{:02} {:02}:{:02}:{:02}", year, month, day, time / 3600, time % 3600 / 60, time % 60)
+}
+
+/// Year, month and day from days since the epoch, after Howard Hinnant's
+/// algorithm.
+fn civil_date(days: u64) -> (i64, i64, i64) {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{check_down, stacked_game, stacks};
+
+    const DECK: &str = "2c 3d 4h 7c 8d 9h As Ks Qs Js 10s";
+
+    #[test]
+    fn exports_a_hand_won_without_a_call() {
+        let mut game = stacked_game(&[100, 100, 100], DECK);
+        game.player_bet(0, 30).unwrap();
+        game.player_fold(1).unwrap();
+        game.player_fold(2).unwrap();
+        let mut history = game.last_hand.take().unwrap();
+        history.started_at = 1_700_000_000;
+
+        let expected = "\
+PokerStars Hand #1: Hold'em No Limit (5/10) - 2023/11/14 22:13:20 UTC
+Table 'Test' 6-max Seat #1 is the button
+Seat 1: Player 1 (100 in chips)
+Seat 2: Player 2 (100 in chips)
+Seat 3: Player 3 (100 in chips)
+Player 2: posts small blind 5
+Player 3: posts big blind 10
+*** HOLE CARDS ***
+Dealt to Player 1 [4h 9h]
+Player 1: raises 20 to 30
+Player 2: folds
+Player 3: folds
+Uncalled bet (20) returned to Player 1
+Player 1 collected 25 from pot
+*** SUMMARY ***
+Total pot 25 | Rake 0
+Seat 1: Player 1 (button) collected (25)
+Seat 2: Player 2 (small blind) folded before Flop
+Seat 3: Player 3 (big blind) folded before Flop
+";
+        assert_eq!(history.to_pokerstars("Test", 6, Some(0)), expected);
+    }
+
+    #[test]
+    fn replay_ends_with_the_same_stacks() {
+        let mut game = stacked_game(&[100, 100, 100], DECK);
+        game.player_call(0).unwrap();
+        game.player_call(1).unwrap();
+        game.player_check(2).unwrap();
+        game.player_bet(1, 20).unwrap();
+        game.player_fold(2).unwrap();
+        game.player_call(0).unwrap();
+        check_down(&mut game);
+        let history = game.last_hand.take().unwrap();
+
+        // One frame for the deal and one for each action
+        let frames = history.replay().unwrap();
+        let played = history.actions.iter().filter(|a| matches!(a.kind, HandActionKind::Acted(_))).count();
+        assert_eq!(frames.len(), played + 1);
+        assert_eq!(stacks(frames.last().unwrap()), stacks(&game));
+        assert_eq!(frames.last().unwrap().pot_awards, game.pot_awards);
+    }
+}
@@ -0,0 +1,25 @@
+//! Shared Texas Hold'em rules engine used by both the websocket backend and
+//! the macroquad client.
+
+pub mod action;
+pub mod betting;
+pub mod event;
+pub mod fair;
+pub mod game;
+pub mod hand;
+pub mod history;
+pub mod pot;
+pub mod view;
+
+#[cfg(test)]
+mod testing;
+
+pub use action::{ActionError, ActionOutcome, LegalAction};
+pub use betting::BettingStructure;
+pub use event::{BlindKind, GameEvent, PlayerAction, ShownHand};
+pub use fair::SeedReveal;
+pub use game::{Blinds, Card, Deck, Game, GameState, Player, Rank, Round, SeatStatus};
+pub use hand::{evaluate, HandCategory, HandStrength};
+pub use history::{HandAction, HandActionKind, HandHistory, ReplayError, SeatRecord};
+pub use pot::{build_pots, Pot, PotAward};
+pub use view::{PlayerView, SeatView};
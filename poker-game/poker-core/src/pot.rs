@@ -0,0 +1,125 @@
+use serde::{Serialize, Deserialize};
+
+use crate::game::Player;
+
+/// A main or side pot and the players who can win it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Pot {
+    pub amount: u32,
+    pub eligible_players: Vec<usize>,
+}
+
+/// Chips paid out of a single pot at the end of a hand.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PotAward {
+    pub pot_index: usize,
+    /// Winners from the left of the button, the order odd chips go in.
+    pub winners: Vec<usize>,
+    pub amount: u32,
+}
+
+/// Splits the chips committed this hand into a main pot followed by side pots.
+///
+/// Each distinct contribution level among players still in the hand caps a
+/// pot, so a player who is all-in for less can only win what every other
+/// player matched. Chips from folded players go into whichever pots their
+/// contribution reaches.
+pub fn build_pots(players: &[Player]) -> Vec<Pot> {
+    let mut levels: Vec<u32> = players.iter()
+        .filter(|p| !p.has_folded && p.contributed > 0)
+        .map(|p| p.contributed)
+        .collect();
+    levels.sort_unstable();
+    levels.dedup();
+
+    let mut pots: Vec<Pot> = vec![];
+    let mut previous_level = 0;
+    for level in levels {
+        let amount: u32 = players.iter()
+            .map(|p| p.contributed.min(level) - p.contributed.min(previous_level))
+            .sum();
+        let eligible_players = players.iter()
+            .enumerate()
+            .filter(|(_, p)| !p.has_folded && p.contributed >= level)
+            .map(|(i, _)| i)
+            .collect();
+        pots.push(Pot { amount, eligible_players });
+        previous_level = level;
+    }
+
+    // Folded players may have put in more than anyone still in the hand
+    let leftover: u32 = players.iter()
+        .map(|p| p.contributed - p.contributed.min(previous_level))
+        .sum();
+    if leftover > 0 {
+        match pots.last_mut() {
+            Some(pot) => pot.amount += leftover,
+            None => pots.push(Pot { amount: leftover, eligible_players: vec![] }),
+        }
+    }
+
+    pots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{GameState, SeatStatus};
+    use crate::testing::{check_down, stacked_game, stacks};
+
+    fn player(contributed: u32, has_folded: bool) -> Player {
+        Player {
+            name: String::new(),
+            chips: 0,
+            hand: vec![],
+            has_folded,
+            has_acted: true,
+            contributed,
+            street_contribution: 0,
+            is_all_in: !has_folded,
+            status: SeatStatus::Playing,
+            facing_incomplete_raise: false,
+        }
+    }
+
+    #[test]
+    fn folded_chips_go_into_the_pots_they_reach() {
+        // Seat 1 folded after putting in more than the short stack at seat 0
+        let players = [player(20, false), player(60, true), player(100, false), player(100, false)];
+        let pots = build_pots(&players);
+        assert_eq!(pots, vec![
+            Pot { amount: 80, eligible_players: vec![0, 2, 3] },
+            Pot { amount: 200, eligible_players: vec![2, 3] },
+        ]);
+    }
+
+    #[test]
+    fn split_pot_gives_the_odd_chip_left_of_the_button() {
+        // The board plays, so both players left in split 25 chips
+        let mut game = stacked_game(&[100, 100, 100], "2c 3d 4h 7c 8d 9h As Ks Qs Js 10s");
+        let button = game.current_player_index;
+        game.player_call(button).unwrap();
+        game.player_fold(1).unwrap();
+        check_down(&mut game);
+
+        assert_eq!(game.pot_awards[0].amount, 25);
+        assert_eq!(game.pot_awards[0].winners, vec![2, 0]);
+        assert_eq!(stacks(&game), vec![102, 95, 103]);
+    }
+
+    #[test]
+    fn three_way_all_in_builds_side_pots() {
+        // Player 1 has aces, Player 2 kings and Player 3 queens
+        let mut game = stacked_game(&[50, 100, 200], "Kd Qd Ad Kc Qc Ac 2h 7s 9c Jd 3s");
+        while game.state == GameState::PlayerTurn {
+            let player = game.current_player_index;
+            game.player_all_in(player).unwrap();
+        }
+
+        let pots: Vec<(u32, Vec<usize>)> = game.pot_awards.iter()
+            .map(|award| (award.amount, award.winners.clone()))
+            .collect();
+        assert_eq!(pots, vec![(150, vec![0]), (100, vec![1]), (100, vec![2])]);
+        assert_eq!(stacks(&game), vec![150, 100, 100]);
+    }
+}
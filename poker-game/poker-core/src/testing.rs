@@ -0,0 +1,61 @@
+//! Helpers for tests that play out hands from a known deck.
+
+use crate::game::{Card, Deck, Game, GameState, Rank};
+
+/// Cards written like `Ah 10d Ks`.
+pub fn cards(text: &str) -> Vec<Card> {
+    text.split_whitespace()
+        .map(|card| {
+            let (rank, suit) = card.split_at(card.len() - 1);
+            let rank = match rank {
+                "J" => Rank::Jack,
+                "Q" => Rank::Queen,
+                "K" => Rank::King,
+                "A" => Rank::Ace,
+                n => Rank::Number(n.parse().unwrap()),
+            };
+            let suit = match suit {
+                "h" => '♥',
+                "d" => '♦',
+                "c" => '♣',
+                _ => '♠',
+            };
+            Card { suit, rank }
+        })
+        .collect()
+}
+
+/// A game with a player per stack, waiting for the first deal.
+pub fn seated_game(stacks: &[u32]) -> Game {
+    let mut game = Game::with_seed(0);
+    for (i, &chips) in stacks.iter().enumerate() {
+        game.add_player(format!("Player {}", i + 1), chips);
+    }
+    game
+}
+
+/// A game with a player per stack, dealt from `deck` with the button on seat 0.
+pub fn stacked_game(stacks: &[u32], deck: &str) -> Game {
+    let mut game = seated_game(stacks);
+    game.deal_cards_from(Deck::from_order(cards(deck)));
+    game
+}
+
+/// Plays the hand out with everyone checking, or calling if they can't.
+pub fn check_down(game: &mut Game) {
+    while game.state == GameState::PlayerTurn {
+        let player = game.current_player_index;
+        if game.player_check(player).is_err() {
+            game.player_call(player).unwrap();
+        }
+    }
+}
+
+pub fn stacks(game: &Game) -> Vec<u32> {
+    game.players.iter().map(|p| p.chips).collect()
+}
+
+/// Chips in stacks and in the pot, which no action should change.
+pub fn total_chips(game: &Game) -> u32 {
+    game.players.iter().map(|p| p.chips).sum::<u32>() + game.pot
+}
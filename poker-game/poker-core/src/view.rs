@@ -0,0 +1,130 @@
+//! What a single player is allowed to see of a game.
+
+use serde::{Serialize, Deserialize};
+
+use crate::action::LegalAction;
+use crate::betting::BettingStructure;
+use crate::game::{Blinds, Card, Game, GameState, Player, Round, SeatStatus};
+use crate::pot::PotAward;
+
+/// A player as seen by someone else at the table.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SeatView {
+    pub name: String,
+    pub chips: u32,
+    /// Hole cards, or `None` if the viewer isn't allowed to see them.
+    pub hand: Option<Vec<Card>>,
+    pub has_folded: bool,
+    pub has_acted: bool,
+    pub contributed: u32,
+    pub street_contribution: u32,
+    pub is_all_in: bool,
+    pub status: SeatStatus,
+}
+
+/// The game from one player's seat: their own hole cards, anyone else's only
+/// once shown down, and never the deck.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlayerView {
+    /// The viewer's seat, or `None` for a spectator.
+    pub seat: Option<usize>,
+    pub players: Vec<SeatView>,
+    pub community_cards: Vec<Card>,
+    pub current_bet: u32,
+    pub pot: u32,
+    pub current_player_index: usize,
+    pub state: GameState,
+    pub round: Round,
+    pub pot_awards: Vec<PotAward>,
+    pub blinds: Blinds,
+    pub betting_structure: BettingStructure,
+    pub dealer_index: usize,
+    pub small_blind_index: usize,
+    pub big_blind_index: usize,
+    pub hand_number: u32,
+    /// What the viewer may do right now, empty unless it's their turn.
+    pub legal_actions: Vec<LegalAction>,
+}
+
+impl SeatView {
+    fn new(player: &Player, show_hand: bool) -> Self {
+        SeatView {
+            name: player.name.clone(),
+            chips: player.chips,
+            hand: show_hand.then(|| player.hand.clone()),
+            has_folded: player.has_folded,
+            has_acted: player.has_acted,
+            contributed: player.contributed,
+            street_contribution: player.street_contribution,
+            is_all_in: player.is_all_in,
+            status: player.status,
+        }
+    }
+}
+
+impl Game {
+    /// Projects the game for `viewer`, hiding everything they shouldn't see.
+    ///
+    /// Opponents' cards are only shown for players still in the hand at
+    /// showdown; a hand won by everyone else folding stays hidden.
+    pub fn view_for(&self, viewer: Option<usize>) -> PlayerView {
+        let showdown = self.state == GameState::ShowDown;
+        let players = self.players.iter()
+            .enumerate()
+            .map(|(i, p)| SeatView::new(p, viewer == Some(i) || (showdown && !p.has_folded)))
+            .collect();
+        let legal_actions = match viewer {
+            Some(seat) => self.legal_actions(seat),
+            None => vec![],
+        };
+
+        PlayerView {
+            seat: viewer,
+            players,
+            community_cards: self.community_cards.clone(),
+            current_bet: self.current_bet,
+            pot: self.pot,
+            current_player_index: self.current_player_index,
+            state: self.state.clone(),
+            round: self.round.clone(),
+            pot_awards: self.pot_awards.clone(),
+            blinds: self.blinds.clone(),
+            betting_structure: self.betting_structure.clone(),
+            dealer_index: self.dealer_index,
+            small_blind_index: self.small_blind_index,
+            big_blind_index: self.big_blind_index,
+            hand_number: self.hand_number,
+            legal_actions,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{cards, check_down, stacked_game};
+
+    fn shown(view: &PlayerView) -> Vec<bool> {
+        view.players.iter().map(|seat| seat.hand.is_some()).collect()
+    }
+
+    #[test]
+    fn hole_cards_stay_hidden_until_showdown() {
+        let mut game = stacked_game(&[100, 100, 100], "2c 3d 4h 7c 8d 9h As Ks Qs Js 10s");
+        game.player_call(0).unwrap();
+        game.player_fold(1).unwrap();
+
+        let own = game.view_for(Some(0));
+        assert_eq!(shown(&own), vec![true, false, false]);
+        assert_eq!(own.players[0].hand, Some(cards("4h 9h")));
+        assert_eq!(shown(&game.view_for(None)), vec![false, false, false]);
+        // The view has no deck, so the board only shows once it's dealt
+        assert!(own.community_cards.is_empty());
+
+        // Only the hands still in are shown down, to players and spectators alike
+        check_down(&mut game);
+        assert_eq!(game.state, GameState::ShowDown);
+        assert_eq!(shown(&game.view_for(None)), vec![true, false, true]);
+        assert_eq!(shown(&game.view_for(Some(1))), vec![true, true, true]);
+    }
+}